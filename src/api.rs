@@ -0,0 +1,146 @@
+use std::env;
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serenity::all::UserId;
+
+use crate::cmds::{enter_result, get_now};
+use crate::data::{Bet, Data, DataInter, Game, Team};
+use crate::scoring::{standings, user_tallies};
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub user: UserId,
+    pub name: Option<String>,
+    pub points: u32,
+    pub exact: u32,
+    pub tendency: u32,
+    pub global: u32,
+}
+
+#[derive(Deserialize)]
+struct ResultBody {
+    team1: u16,
+    team2: u16,
+    #[serde(default)]
+    msg: String,
+}
+
+/// Serves the API and the leaderboard page on `API_PORT` if it is set. Admin endpoints need
+/// `Authorization: Bearer <API_TOKEN>` and are disabled without a token.
+pub async fn serve(data: Data) {
+    let Ok(port) = env::var("API_PORT") else {
+        return;
+    };
+    let Ok(port) = port.parse::<u16>() else {
+        eprintln!("API not started, API_PORT is no valid port: {port}");
+        return;
+    };
+
+    let app = Router::new()
+        .route("/", get(crate::web::leaderboard_page))
+        .route("/spiele.ics", get(crate::ical::calendar_feed))
+        .route("/teams", get(teams))
+        .route("/games", get(games))
+        .route("/games/:short/bets", get(bets))
+        .route("/games/:short/result", post(set_result))
+        .route("/leaderboard", get(leaderboard))
+        .with_state(data);
+
+    let listener = match tokio::net::TcpListener::bind(("0.0.0.0", port)).await {
+        Ok(listener) => listener,
+        Err(why) => {
+            eprintln!("API could not bind port {port}: {why:?}");
+            return;
+        }
+    };
+    println!("API listening on port {port}");
+    if let Err(why) = axum::serve(listener, app).await {
+        eprintln!("API error: {why:?}");
+    }
+}
+
+fn error(status: StatusCode, msg: &str) -> Response {
+    (status, msg.to_string()).into_response()
+}
+
+fn authorized(headers: &HeaderMap) -> bool {
+    let Ok(token) = env::var("API_TOKEN") else {
+        return false;
+    };
+    !token.is_empty()
+        && headers
+            .get("Authorization")
+            .and_then(|h| h.to_str().ok())
+            .is_some_and(|h| h == format!("Bearer {token}"))
+}
+
+async fn teams(State(data): State<Data>) -> Json<Vec<Team>> {
+    Json(data.read().await.teams.clone())
+}
+
+async fn games(State(data): State<Data>) -> Json<Vec<Game>> {
+    Json(data.read().await.games.clone())
+}
+
+pub fn leaderboard_entries(d: &DataInter) -> Vec<LeaderboardEntry> {
+    standings(d, &user_tallies(d))
+        .into_iter()
+        .map(|s| LeaderboardEntry {
+            rank: s.rank,
+            user: s.user,
+            name: d.names.get(&s.user).cloned(),
+            points: s.tally.points,
+            exact: s.tally.exact,
+            tendency: s.tally.tendency,
+            global: s.tally.global,
+        })
+        .collect()
+}
+
+async fn leaderboard(State(data): State<Data>) -> Json<Vec<LeaderboardEntry>> {
+    let d = data.read().await;
+    Json(leaderboard_entries(&d))
+}
+
+async fn bets(
+    State(data): State<Data>,
+    Path(short): Path<String>,
+) -> Result<Json<Vec<Bet>>, Response> {
+    let d = data.read().await;
+    let game = d
+        .games
+        .iter()
+        .find(|g| g.short == short)
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, "Kürzel gehört zu keinem Spiel"))?;
+    if game.start_time > get_now() {
+        return Err(error(
+            StatusCode::FORBIDDEN,
+            "Tipps werden erst bei Anpfiff aufgedeckt",
+        ));
+    }
+
+    Ok(Json(d.bets.get(&short).cloned().unwrap_or_default()))
+}
+
+async fn set_result(
+    State(data): State<Data>,
+    Path(short): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<ResultBody>,
+) -> Result<Json<Game>, Response> {
+    if !authorized(&headers) {
+        return Err(error(StatusCode::UNAUTHORIZED, "Ungültiger Token"));
+    }
+
+    let mut d = data.lock().await;
+    let game = enter_result(&mut d, &short, (body.team1, body.team2, body.msg))
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, "Kürzel gehört zu keinem Spiel"))?;
+
+    Ok(Json(game))
+}
@@ -0,0 +1,331 @@
+use std::collections::HashSet;
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use ascii_table::AsciiTable;
+use chrono::NaiveDateTime;
+use clap::{Parser, Subcommand};
+use itertools::Itertools;
+
+use tippy_tappy::cmds::apply_result;
+use tippy_tappy::data::{default_modifier, read_save_file, write_save_file, DataInter, Game, Team};
+use tippy_tappy::export::write_csv_files;
+use tippy_tappy::scoring::{standings, user_tallies};
+use tippy_tappy::Error;
+
+/// Maintenance tool working directly on the save file in `SAVE_FILE`.
+/// Stop the bot first, it keeps its own copy in memory and overwrites changes.
+#[derive(Parser)]
+#[command(name = "tippy-admin")]
+struct Cli {
+    #[command(subcommand)]
+    command: Cmd,
+}
+
+#[derive(Subcommand)]
+enum Cmd {
+    /// List all teams
+    Teams,
+    /// List all games
+    Games,
+    /// Add a team
+    AddTeam {
+        iso: String,
+        name: String,
+        flag: String,
+    },
+    /// Change name or flag of a team
+    EditTeam {
+        iso: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        flag: Option<String>,
+    },
+    /// Add a game, kickoff as "YYYY MM DD HH:MM"
+    AddGame {
+        short: String,
+        name: String,
+        team1: String,
+        team2: String,
+        kickoff: String,
+        #[arg(long)]
+        modifier: Option<u32>,
+        #[arg(long)]
+        matchday: Option<String>,
+    },
+    /// Change a game, kickoff as "YYYY MM DD HH:MM"
+    EditGame {
+        short: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        team1: Option<String>,
+        #[arg(long)]
+        team2: Option<String>,
+        #[arg(long)]
+        kickoff: Option<String>,
+        #[arg(long)]
+        modifier: Option<u32>,
+        #[arg(long)]
+        matchday: Option<String>,
+    },
+    /// Enter the result of a game
+    Score {
+        short: String,
+        team1: u16,
+        team2: u16,
+        #[arg(long)]
+        msg: Option<String>,
+    },
+    /// Print the leaderboard
+    Leaderboard,
+    /// Check the save file for inconsistencies
+    Validate,
+    /// Write all data as CSV files into a directory
+    Export {
+        #[arg(default_value = "export")]
+        dir: PathBuf,
+    },
+}
+
+fn parse_kickoff(kickoff: &str) -> Result<NaiveDateTime, Error> {
+    Ok(NaiveDateTime::parse_from_str(kickoff, "%Y %m %d %H:%M")?)
+}
+
+fn check_team(d: &DataInter, iso: &str) -> Result<(), Error> {
+    if d.teams.iter().any(|t| t.iso == iso) {
+        Ok(())
+    } else {
+        Err(format!("Unknown team {iso}").into())
+    }
+}
+
+fn game_mut<'a>(d: &'a mut DataInter, short: &str) -> Result<&'a mut Game, Error> {
+    d.games
+        .iter_mut()
+        .find(|g| g.short == short)
+        .ok_or(format!("Unknown game {short}").into())
+}
+
+fn problems(d: &DataInter) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for iso in d.teams.iter().map(|t| &t.iso).duplicates() {
+        problems.push(format!("Team {iso} exists more than once"));
+    }
+    for short in d.games.iter().map(|g| &g.short).duplicates() {
+        problems.push(format!("Game {short} exists more than once"));
+    }
+
+    let teams = d.teams.iter().map(|t| &t.iso).collect::<HashSet<_>>();
+    for game in &d.games {
+        for iso in [&game.team1_iso, &game.team2_iso] {
+            if !teams.contains(iso) {
+                problems.push(format!("Game {} uses unknown team {iso}", game.short));
+            }
+        }
+    }
+
+    for (short, bets) in &d.bets {
+        if !d.games.iter().any(|g| &g.short == short) {
+            problems.push(format!("Bets on unknown game {short}"));
+        }
+        for user in bets.iter().map(|b| b.user).duplicates() {
+            problems.push(format!("User {user} bet more than once on {short}"));
+        }
+    }
+
+    for bet in d.global_bets.values() {
+        let isos = bet
+            .result
+            .iter()
+            .flatten()
+            .chain(bet.bets.iter().flat_map(|b| &b.1));
+        for iso in isos.unique() {
+            if !teams.contains(iso) {
+                problems.push(format!("Global bet {} uses unknown team {iso}", bet.short));
+            }
+        }
+    }
+
+    for pending in &d.pending_results {
+        if !d.games.iter().any(|g| g.short == pending.short) {
+            problems.push(format!("Pending result for unknown game {}", pending.short));
+        }
+    }
+    for short in d.stakes.keys() {
+        if !d.games.iter().any(|g| &g.short == short) {
+            problems.push(format!("Stakes on unknown game {short}"));
+        }
+    }
+
+    problems
+}
+
+fn run(command: Cmd) -> Result<(), Error> {
+    let path = env::var("SAVE_FILE").map_err(|_| "SAVE_FILE is not set")?;
+    let mut d = read_save_file(&path)?;
+
+    match command {
+        Cmd::Teams => {
+            for team in &d.teams {
+                println!("{}\t{}\t{}", team.iso, team.flag, team.name);
+            }
+            return Ok(());
+        }
+        Cmd::Games => {
+            for game in d.games.iter().sorted_by_key(|g| g.start_time) {
+                println!(
+                    "{}\t{}\t{} vs {}\t{}\t{}",
+                    game.short,
+                    game.start_time.format("%Y-%m-%d %H:%M"),
+                    game.team1_iso,
+                    game.team2_iso,
+                    game.result
+                        .as_ref()
+                        .map(|r| format!("{}:{}", r.0, r.1))
+                        .unwrap_or("-:-".to_string()),
+                    game.name
+                );
+            }
+            return Ok(());
+        }
+        Cmd::AddTeam { iso, name, flag } => {
+            if d.teams.iter().any(|t| t.iso == iso) {
+                return Err(format!("Team {iso} already exists").into());
+            }
+            d.teams.push(Team { name, flag, iso });
+        }
+        Cmd::EditTeam { iso, name, flag } => {
+            let team = d
+                .teams
+                .iter_mut()
+                .find(|t| t.iso == iso)
+                .ok_or(format!("Unknown team {iso}"))?;
+            if let Some(name) = name {
+                team.name = name;
+            }
+            if let Some(flag) = flag {
+                team.flag = flag;
+            }
+        }
+        Cmd::AddGame {
+            short,
+            name,
+            team1,
+            team2,
+            kickoff,
+            modifier,
+            matchday,
+        } => {
+            if d.games.iter().any(|g| g.short == short) {
+                return Err(format!("Game {short} already exists").into());
+            }
+            check_team(&d, &team1)?;
+            check_team(&d, &team2)?;
+            d.games.push(Game {
+                name,
+                short,
+                team1_iso: team1,
+                team2_iso: team2,
+                start_time: parse_kickoff(&kickoff)?,
+                result: None,
+                modifier: modifier.unwrap_or(default_modifier()),
+                matchday,
+                external_id: None,
+            });
+        }
+        Cmd::EditGame {
+            short,
+            name,
+            team1,
+            team2,
+            kickoff,
+            modifier,
+            matchday,
+        } => {
+            for iso in team1.iter().chain(team2.iter()) {
+                check_team(&d, iso)?;
+            }
+            let kickoff = kickoff.as_deref().map(parse_kickoff).transpose()?;
+            let game = game_mut(&mut d, &short)?;
+            if let Some(name) = name {
+                game.name = name;
+            }
+            if let Some(team1) = team1 {
+                game.team1_iso = team1;
+            }
+            if let Some(team2) = team2 {
+                game.team2_iso = team2;
+            }
+            if let Some(kickoff) = kickoff {
+                game.start_time = kickoff;
+            }
+            if let Some(modifier) = modifier {
+                game.modifier = modifier;
+            }
+            if matchday.is_some() {
+                game.matchday = matchday;
+            }
+        }
+        Cmd::Score {
+            short,
+            team1,
+            team2,
+            msg,
+        } => {
+            apply_result(&mut d, &short, (team1, team2, msg.unwrap_or_default()))
+                .ok_or(format!("Unknown game {short}"))?;
+        }
+        Cmd::Leaderboard => {
+            let mut table = AsciiTable::default();
+            table.column(0).set_header("#");
+            table.column(1).set_header("Spieler");
+            table.column(2).set_header("Punkte");
+            let data = standings(&d, &user_tallies(&d))
+                .into_iter()
+                .map(|s| {
+                    vec![
+                        format!("{}.", s.rank),
+                        d.names.get(&s.user).cloned().unwrap_or(s.user.to_string()),
+                        s.tally.points.to_string(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            table.print(data);
+            return Ok(());
+        }
+        Cmd::Validate => {
+            let problems = problems(&d);
+            if problems.is_empty() {
+                println!("No problems found");
+                return Ok(());
+            }
+            for problem in &problems {
+                println!("{problem}");
+            }
+            return Err(format!("{} problem(s) found", problems.len()).into());
+        }
+        Cmd::Export { dir } => {
+            write_csv_files(&d, &dir)?;
+            println!("Exported to {}", dir.display());
+            return Ok(());
+        }
+    }
+
+    write_save_file(&path, &d)
+}
+
+fn main() -> ExitCode {
+    dotenv::dotenv().ok();
+
+    match run(Cli::parse().command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(why) => {
+            eprintln!("{why}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use ascii_table::AsciiTable;
+use itertools::Itertools;
+use poise::{Command, CreateReply};
+use rand::Rng;
+use serenity::all::UserId;
+
+use crate::cmds::get_now;
+use crate::data::{Data, DataInter, Game};
+use crate::scoring::{bet_points, user_points};
+use crate::{Error, PoiseContext, JOKER_FACTOR, POINTS_CORRECT};
+
+const SIMULATIONS: u32 = 10_000;
+const MAX_GOALS: u16 = 4;
+const AVERAGE_GOALS: f64 = 1.35;
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![chancen()]
+}
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+enum Model {
+    #[name = "Gleichverteilt"]
+    Uniform,
+    #[name = "Teamstärke"]
+    Rating,
+}
+
+/// Average goal difference of each team in the finished games.
+fn ratings(d: &DataInter) -> HashMap<&str, f64> {
+    let mut diffs: HashMap<&str, Vec<f64>> = HashMap::new();
+    for game in &d.games {
+        let Some(result) = &game.result else {
+            continue;
+        };
+        let diff = result.0 as f64 - result.1 as f64;
+        diffs.entry(&game.team1_iso).or_default().push(diff);
+        diffs.entry(&game.team2_iso).or_default().push(-diff);
+    }
+    diffs
+        .into_iter()
+        .map(|(iso, d)| (iso, d.iter().sum::<f64>() / d.len() as f64))
+        .collect()
+}
+
+fn poisson(rng: &mut impl Rng, lambda: f64) -> u16 {
+    let limit = (-lambda).exp();
+    let mut product = rng.gen::<f64>();
+    let mut goals = 0;
+    while product > limit {
+        product *= rng.gen::<f64>();
+        goals += 1;
+    }
+    goals
+}
+
+fn simulate(
+    rng: &mut impl Rng,
+    model: Model,
+    ratings: &HashMap<&str, f64>,
+    game: &Game,
+) -> (u16, u16) {
+    match model {
+        Model::Uniform => (rng.gen_range(0..=MAX_GOALS), rng.gen_range(0..=MAX_GOALS)),
+        Model::Rating => {
+            let diff = ratings.get(game.team1_iso.as_str()).unwrap_or(&0.0)
+                - ratings.get(game.team2_iso.as_str()).unwrap_or(&0.0);
+            (
+                poisson(rng, AVERAGE_GOALS * (0.25 * diff).exp()),
+                poisson(rng, AVERAGE_GOALS * (-0.25 * diff).exp()),
+            )
+        }
+    }
+}
+
+/// Upper bound of the points a user can still get on an open game. Tips on
+/// games that did not start yet can still change, so those assume the best
+/// case of every scoring option.
+fn max_points(d: &DataInter, game: &Game, user: UserId) -> u32 {
+    if game.start_time > get_now() {
+        let mut points = POINTS_CORRECT * game.modifier;
+        if d.settings.jokers > 0 {
+            points *= JOKER_FACTOR;
+        }
+        if d.settings.confidence {
+            let matchday = game.matchday_name();
+            points *= d
+                .games
+                .iter()
+                .filter(|g| g.matchday_name() == matchday)
+                .count() as u32;
+        }
+        if d.settings.rarity {
+            points *= 2;
+        }
+        return points;
+    }
+
+    let Some(bet) = d
+        .bets
+        .get(&game.short)
+        .and_then(|bets| bets.iter().find(|b| b.user == user))
+    else {
+        return 0;
+    };
+    let mut exact = game.clone();
+    exact.result = Some((bet.team1, bet.team2, String::new()));
+    bet_points(d, &exact, bet).unwrap_or(0)
+}
+
+fn max_global_points(d: &DataInter) -> u32 {
+    d.global_bets
+        .values()
+        .filter(|b| b.result.is_none())
+        .map(|b| {
+            if b.partial {
+                b.points as u32 * b.picks as u32
+            } else {
+                b.points as u32
+            }
+        })
+        .sum()
+}
+
+struct Chance {
+    user: UserId,
+    points: u32,
+    max: u32,
+    first: f64,
+    top3: f64,
+}
+
+fn chances(d: &DataInter, model: Model) -> Vec<Chance> {
+    let points = user_points(d);
+    let open = d
+        .games
+        .iter()
+        .filter(|g| g.result.is_none())
+        .collect::<Vec<_>>();
+    let ratings = ratings(d);
+    let users = points.keys().copied().collect::<Vec<_>>();
+
+    let mut first: HashMap<UserId, f64> = HashMap::new();
+    let mut top3: HashMap<UserId, u32> = HashMap::new();
+    let mut rng = rand::thread_rng();
+    for _ in 0..SIMULATIONS {
+        let mut simulated = points.clone();
+        for game in &open {
+            let (team1, team2) = simulate(&mut rng, model, &ratings, game);
+            let mut game = (*game).clone();
+            game.result = Some((team1, team2, String::new()));
+            for bet in d.bets.get(&game.short).into_iter().flatten() {
+                *simulated.entry(bet.user).or_default() += bet_points(d, &game, bet).unwrap_or(0);
+            }
+        }
+
+        let best = simulated.values().max().copied().unwrap_or(0);
+        let leaders = simulated.values().filter(|p| **p == best).count();
+        for user in &users {
+            let p = simulated[user];
+            if p == best {
+                *first.entry(*user).or_default() += 1.0 / leaders as f64;
+            }
+            if simulated.values().filter(|o| **o > p).count() < 3 {
+                *top3.entry(*user).or_default() += 1;
+            }
+        }
+    }
+
+    let global = max_global_points(d);
+    users
+        .into_iter()
+        .map(|user| Chance {
+            user,
+            points: points[&user],
+            max: points[&user] + global + open.iter().map(|g| max_points(d, g, user)).sum::<u32>(),
+            first: first.get(&user).copied().unwrap_or(0.0) / SIMULATIONS as f64,
+            top3: *top3.get(&user).unwrap_or(&0) as f64 / SIMULATIONS as f64,
+        })
+        .sorted_by(|a, b| b.first.total_cmp(&a.first).then(b.points.cmp(&a.points)))
+        .collect()
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn chancen(
+    ctx: PoiseContext<'_>,
+    #[description = "Wie die offenen Spiele ausgehen (Standard: Teamstärke)"] modell: Option<Model>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    // Simulating takes a while, so it runs on a copy without blocking the data.
+    let d = ctx.data().read().await.clone();
+    let model = modell.unwrap_or(Model::Rating);
+    let chances = tokio::task::spawn_blocking(move || chances(&d, model)).await?;
+    let leader = chances.iter().map(|c| c.points).max().unwrap_or(0);
+
+    let mut table = AsciiTable::default();
+    table.column(0).set_header("Spieler");
+    table.column(1).set_header("Punkte");
+    table.column(2).set_header("Max");
+    table.column(3).set_header("Titel");
+    table.column(4).set_header("Top 3");
+
+    let mut data: Vec<Vec<String>> = Vec::new();
+    for chance in &chances {
+        let name = chance
+            .user
+            .to_user(ctx.http())
+            .await
+            .map(|u| u.name)
+            .unwrap_or("UNKNOWN".to_string());
+        data.push(vec![
+            name,
+            chance.points.to_string(),
+            chance.max.to_string(),
+            if chance.max < leader {
+                "raus".to_string()
+            } else {
+                format!("{:.1}%", chance.first * 100.0)
+            },
+            format!("{:.1}%", chance.top3 * 100.0),
+        ]);
+    }
+
+    ctx.send(CreateReply::default().ephemeral(true).content(format!(
+        "# Titelchancen\n{SIMULATIONS} Simulationen der offenen Spiele, noch nicht abgegebene Tipps zählen dabei 0 Punkte. \"raus\" heißt, der Titel ist selbst mit allen maximal möglichen Punkten nicht mehr erreichbar.\n```\n{}\n```",
+        table.format(data)
+    )))
+    .await?;
+
+    Ok(())
+}
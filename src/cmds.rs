@@ -1,27 +1,50 @@
-use ascii_table::{Align, AsciiTable};
+use ascii_table::AsciiTable;
 use chrono::NaiveDateTime;
 use itertools::Itertools;
 use poise::futures_util::StreamExt;
-use poise::{Command, CreateReply};
+use poise::{ChoiceParameter, Command, CreateReply};
 use serenity::all::{
-    Channel, Colour, CreateEmbed, CreateEmbedAuthor, CreateMessage, GetMessages, User,
+    Channel, Colour, CreateEmbed, CreateEmbedAuthor, CreateMessage, GetMessages, User, UserId,
 };
 use serenity::futures::Stream;
-use std::collections::HashMap;
-use std::fmt::Display;
-use std::ops::Sub;
+use std::collections::HashSet;
 
-use crate::data::{Bet, Data, Game, GlobalBet, Team};
-use crate::{Error, PoiseContext, POINTS_CORRECT, POINTS_TEAM, POINTS_TENDENZ};
+use crate::api::leaderboard_entries;
+use crate::data::{Bet, Data, DataInter, Game, GlobalBet, Outcome, Team, TieBreaker};
+use crate::history::{filtered, leader_line, movement, previous_tallies};
+use crate::leagues::{league_autocomplete, league_members, participants};
+use crate::scoring::{bet_points, ranks, standings, timeline, user_tallies, Tally};
+use crate::webhooks::{self, Event};
+use crate::{Error, PoiseContext, UTC_OFFSET_HOURS};
 
 pub fn get_now() -> NaiveDateTime {
-    chrono::Local::now()
-        .naive_local()
-        .sub(chrono::Duration::hours(-2))
+    chrono::Local::now().naive_local() + chrono::Duration::hours(UTC_OFFSET_HOURS)
+}
+
+fn team_names(teams: &[Team], isos: &[String]) -> String {
+    isos.iter()
+        .map(|iso| {
+            teams
+                .iter()
+                .find(|t| &t.iso == iso)
+                .map(|t| t.name.clone())
+                .unwrap_or("ERROR".to_string())
+        })
+        .join(", ")
+}
+
+pub fn format_tip(bet: &Bet) -> String {
+    format!(
+        "{}:{}{}{}",
+        bet.team1,
+        bet.team2,
+        if bet.joker { "*" } else { "" },
+        bet.weight.map(|w| format!(" x{w}")).unwrap_or_default()
+    )
 }
 
 pub fn get_cmds() -> Vec<Command<Data, Error>> {
-    vec![
+    let mut cmds = vec![
         age(),
         get_teams(),
         add_team(),
@@ -30,11 +53,34 @@ pub fn get_cmds() -> Vec<Command<Data, Error>> {
         add_score(),
         bet(),
         get_bets(),
+        spiel(),
         print_overview(),
+        rangliste(),
+        was_waere_wenn(),
         add_global_bet(),
         bet_global(),
         add_global_score(),
-    ]
+        set_jokers(),
+        set_confidence(),
+        set_rarity(),
+        set_tiebreakers(),
+    ];
+    cmds.extend(crate::survivor::get_cmds());
+    cmds.extend(crate::coins::get_cmds());
+    cmds.extend(crate::duels::get_cmds());
+    cmds.extend(crate::leagues::get_cmds());
+    cmds.extend(crate::squads::get_cmds());
+    cmds.extend(crate::stats::get_cmds());
+    cmds.extend(crate::history::get_cmds());
+    cmds.extend(crate::tiebreak::get_cmds());
+    cmds.extend(crate::virtuals::get_cmds());
+    cmds.extend(crate::chances::get_cmds());
+    cmds.extend(crate::ical::get_cmds());
+    cmds.extend(crate::webhooks::get_cmds());
+    cmds.extend(crate::results::get_cmds());
+    cmds.extend(crate::openfootball::get_cmds());
+    cmds.extend(crate::export::get_cmds());
+    cmds
 }
 
 #[poise::command(slash_command)]
@@ -98,6 +144,7 @@ async fn add_team(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 #[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
 async fn add_game(
     ctx: PoiseContext<'_>,
@@ -107,6 +154,7 @@ async fn add_game(
     #[description = "Team 2"] team2: String,
     #[description = "Anpfiff"] start_time: String,
     #[description = "Modifier"] modifier: Option<u32>,
+    #[description = "Spieltag"] matchday: Option<String>,
 ) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
     let mut d = ctx.data().lock().await;
@@ -129,7 +177,11 @@ async fn add_game(
         result: None,
         start_time: t,
         modifier: modifier.unwrap_or(crate::data::default_modifier()),
+        matchday,
+        external_id: None,
     });
+    let game = d.games.last().cloned().ok_or("Game could not be added")?;
+    webhooks::emit(&d, Event::GameCreated { game });
 
     ctx.reply("Succesful").await.unwrap();
 
@@ -151,10 +203,9 @@ async fn list_games(ctx: PoiseContext<'_>) -> Result<(), Error> {
     games_table.column(4).set_header("Anpfiff");
     games_table.column(5).set_header("Ergebnis");
 
-    let mut games = d.games.iter().cloned().collect::<Vec<_>>();
+    let mut games = d.games.to_vec();
 
-    games.sort_by(|g1, g2| g1.start_time.cmp(&g2.start_time));
-    games.sort_by(|g1, g2| g1.start_time.cmp(&g2.start_time));
+    games.sort_by_key(|g| g.start_time);
 
     let mut games_table_data: Vec<Vec<String>> = Vec::new();
     for game in games {
@@ -201,16 +252,23 @@ async fn add_score(
     #[description = "Die Tore von Team 1"] team1_score: u16,
     #[description = "Die Tore von Team 2"] team2_score: u16,
     #[description = "Extra informationen zum Spiel"] msg: Option<String>,
+    #[description = "Ergebnis im Kanal verkünden (Standard: wenn es Seltenheit, Coins oder Survivor betrifft)"]
+    announce: Option<bool>,
 ) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
     let mut d = ctx.data().lock().await;
-    let g = d
-        .games
-        .iter_mut()
-        .find(|g| g.short == short)
-        .ok_or("Kürzel gehört zu keinem Spiel")?;
+    enter_result(
+        &mut d,
+        &short,
+        (team1_score, team2_score, msg.unwrap_or_default()),
+    )
+    .ok_or("Kürzel gehört zu keinem Spiel")?;
 
-    g.result = Some((team1_score, team2_score, msg.unwrap_or(String::new())));
+    if announce.unwrap_or_else(|| has_news(&d, &short)) {
+        ctx.channel_id()
+            .say(&ctx, result_announcement(&d, &short))
+            .await?;
+    }
 
     ctx.defer_ephemeral().await?;
     ctx.reply("Succesful").await?;
@@ -218,7 +276,100 @@ async fn add_score(
     Ok(())
 }
 
-async fn game_autocomplete<'a>(
+/// Sets the result of a game and settles everything depending on it.
+pub fn apply_result(d: &mut DataInter, short: &str, result: (u16, u16, String)) -> Option<Game> {
+    let g = d.games.iter_mut().find(|g| g.short == short)?;
+
+    g.result = Some(result);
+    let game = g.clone();
+    d.pending_results.retain(|p| p.short != short);
+    crate::coins::settle(d, short);
+
+    Some(game)
+}
+
+/// Like `apply_result`, but also notifies the webhooks.
+pub fn enter_result(d: &mut DataInter, short: &str, result: (u16, u16, String)) -> Option<Game> {
+    let before = leaderboard_entries(d);
+    let game = apply_result(d, short, result)?;
+    webhooks::emit(d, Event::ResultEntered { game: game.clone() });
+    webhooks::emit_leaderboard(d, before);
+
+    Some(game)
+}
+
+/// Whether the announcement has more to say than the score itself.
+pub fn has_news(d: &DataInter, short: &str) -> bool {
+    let Some(game) = d.games.iter().find(|g| g.short == short) else {
+        return false;
+    };
+    (d.settings.rarity && crate::scoring::rarity(d, game).is_some())
+        || d.stakes.get(short).is_some_and(|s| !s.is_empty())
+        || !crate::survivor::eliminated_by(d, short).is_empty()
+}
+
+pub fn result_announcement(d: &DataInter, short: &str) -> String {
+    let Some(game) = d.games.iter().find(|g| g.short == short) else {
+        return String::new();
+    };
+    let Some(result) = &game.result else {
+        return String::new();
+    };
+    let team = |iso: &str| {
+        d.teams
+            .iter()
+            .find(|t| t.iso == iso)
+            .map(|t| format!("{} {}", t.name, t.flag))
+            .unwrap_or(iso.to_string())
+    };
+
+    let mut lines = vec![format!(
+        "# {}: {} {}:{} {}",
+        game.name,
+        team(&game.team1_iso),
+        result.0,
+        result.1,
+        team(&game.team2_iso)
+    )];
+    if !result.2.is_empty() {
+        lines.push(result.2.clone());
+    }
+
+    if d.settings.rarity {
+        if let Some((same, total)) = crate::scoring::rarity(d, game) {
+            lines.push(format!(
+                "{}: {same} von {total} Tipps → Seltenheitsfaktor (2·{total} − {same}) / {total} = {:.2}",
+                Outcome::of(result.0, result.1).name(),
+                (2 * total - same) as f64 / total as f64
+            ));
+        }
+    }
+
+    if let Some(stakes) = d.stakes.get(short).filter(|s| !s.is_empty()) {
+        let pot = stakes.iter().map(|s| s.coins).sum::<u64>();
+        let winners = stakes
+            .iter()
+            .filter(|s| s.outcome == Outcome::of(result.0, result.1))
+            .count();
+        lines.push(if winners == 0 {
+            format!("🪙 Pot {pot} Coins: Niemand lag richtig, alle Einsätze gehen zurück.")
+        } else {
+            format!("🪙 Pot {pot} Coins wird unter {winners} Gewinner(n) aufgeteilt.")
+        });
+    }
+
+    let eliminated = crate::survivor::eliminated_by(d, short);
+    if !eliminated.is_empty() {
+        lines.push("## Survivor".to_string());
+        for (user, reason) in eliminated {
+            lines.push(format!("💀 <@{user}> ist ausgeschieden ({reason})"));
+        }
+    }
+
+    lines.join("\n")
+}
+
+pub async fn game_autocomplete<'a>(
     ctx: PoiseContext<'_>,
     partial: &'a str,
 ) -> impl Stream<Item = String> + 'a {
@@ -238,6 +389,27 @@ async fn game_autocomplete<'a>(
         })
 }
 
+async fn any_game_autocomplete<'a>(
+    ctx: PoiseContext<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = String> + 'a {
+    let d = ctx.data.read().await;
+    let gs = d
+        .games
+        .iter()
+        .sorted_by_key(|g| g.start_time)
+        .cloned()
+        .collect::<Vec<_>>();
+    serenity::futures::stream::iter(gs)
+        .filter(move |n: &Game| serenity::futures::future::ready(n.short.starts_with(partial)))
+        .map(|g| {
+            format!(
+                "{} {} vs {} '{}'",
+                g.name, g.team1_iso, g.team2_iso, g.short
+            )
+        })
+}
+
 #[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
 async fn bet(
     ctx: PoiseContext<'_>,
@@ -250,6 +422,10 @@ async fn bet(
     #[description = "Anzahl Tore Team 2"]
     #[min = 0]
     team2_score: u16,
+    #[description = "Joker setzen (doppelte Punkte)"] joker: Option<bool>,
+    #[description = "Vertrauen in den Tipp (1 = niedrigstes Gewicht)"]
+    #[min = 1]
+    weight: Option<u32>,
 ) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
     let mut d = ctx.data().lock().await;
@@ -275,27 +451,287 @@ async fn bet(
         return Ok(());
     }
 
-    let bets = d.bets.entry(real_game.short.clone()).or_insert(Vec::new());
+    if joker == Some(true) {
+        let allowed = d.settings.jokers;
+        let matchday = real_game.matchday_name();
+        let used = d
+            .bets
+            .iter()
+            .filter(|(short, _)| **short != real_game.short)
+            .filter(|(short, _)| {
+                !d.settings.jokers_per_matchday
+                    || d.games
+                        .iter()
+                        .any(|g| &g.short == *short && g.matchday_name() == matchday)
+            })
+            .filter(|(_, bets)| bets.iter().any(|b| b.user == user && b.joker))
+            .count() as u32;
+
+        if used >= allowed {
+            ctx.reply(if d.settings.jokers_per_matchday {
+                format!("Du hast bereits alle {allowed} Joker für {matchday} gesetzt!")
+            } else {
+                format!("Du hast bereits alle {allowed} Joker gesetzt!")
+            })
+            .await?;
+            return Ok(());
+        }
+    }
+
+    if weight.is_some() && !d.settings.confidence {
+        ctx.reply("Der Vertrauensmodus ist nicht aktiv!").await?;
+        return Ok(());
+    }
+
+    let weight = if d.settings.confidence {
+        match assign_weight(&mut d, &real_game, user, weight) {
+            Ok(w) => Some(w),
+            Err(msg) => {
+                ctx.reply(msg).await?;
+                return Ok(());
+            }
+        }
+    } else {
+        None
+    };
+
+    let bets = d.bets.entry(real_game.short.clone()).or_default();
 
-    if let Some(bet) = bets.iter_mut().find(|b| b.user == user) {
+    let joker = if let Some(bet) = bets.iter_mut().find(|b| b.user == user) {
         bet.team1 = team1_score;
         bet.team2 = team2_score;
+        bet.joker = joker.unwrap_or(bet.joker);
+        bet.weight = weight;
+        bet.joker
     } else {
         bets.push(Bet {
             user,
             team1: team1_score,
             team2: team2_score,
+            joker: joker.unwrap_or(false),
+            weight,
         });
-    }
+        joker.unwrap_or(false)
+    };
 
     ctx.reply(format!(
-        "Bet saved: {} {} vs {}  {}:{}",
-        real_game.name, real_game.team1_iso, real_game.team2_iso, team1_score, team2_score
+        "Bet saved: {} {} vs {}  {}:{}{}{}",
+        real_game.name,
+        real_game.team1_iso,
+        real_game.team2_iso,
+        team1_score,
+        team2_score,
+        if joker { " (Joker)" } else { "" },
+        weight
+            .map(|w| format!(" (Gewicht {w})"))
+            .unwrap_or_default()
     ))
     .await?;
     Ok(())
 }
 
+/// Picks the confidence weight for a user's bet on `game`. Every weight from
+/// 1 to the number of games in the matchday can be used once; choosing a
+/// weight that is taken by another open game swaps the two.
+fn assign_weight(
+    d: &mut DataInter,
+    game: &Game,
+    user: UserId,
+    weight: Option<u32>,
+) -> Result<u32, String> {
+    let matchday = game.matchday_name();
+    let others = d
+        .games
+        .iter()
+        .filter(|g| g.matchday_name() == matchday && g.short != game.short)
+        .map(|g| (g.short.clone(), g.start_time <= get_now()))
+        .collect::<Vec<_>>();
+    let max = others.len() as u32 + 1;
+
+    let user_weight = |d: &DataInter, short: &str| {
+        d.bets
+            .get(short)
+            .and_then(|bets| bets.iter().find(|b| b.user == user))
+            .and_then(|b| b.weight)
+    };
+    let current = user_weight(d, &game.short);
+    let used = others
+        .iter()
+        .filter_map(|(short, started)| user_weight(d, short).map(|w| (short.clone(), w, *started)))
+        .collect::<Vec<_>>();
+    let free = |exclude: u32| {
+        (1..=max)
+            .find(|w| *w != exclude && !used.iter().any(|u| u.1 == *w))
+            .unwrap_or(1)
+    };
+
+    let Some(weight) = weight else {
+        return Ok(current.unwrap_or_else(|| free(0)));
+    };
+
+    if weight > max {
+        return Err(format!(
+            "An {matchday} gibt es nur {max} Spiele, das höchste Gewicht ist {max}!"
+        ));
+    }
+
+    if let Some((short, _, started)) = used.iter().find(|u| u.1 == weight) {
+        if *started {
+            return Err(format!(
+                "Gewicht {weight} ist bereits für das laufende Spiel {short} vergeben!"
+            ));
+        }
+
+        let swapped = current.unwrap_or_else(|| free(weight));
+        if let Some(b) = d
+            .bets
+            .get_mut(short)
+            .and_then(|bets| bets.iter_mut().find(|b| b.user == user))
+        {
+            b.weight = Some(swapped);
+        }
+    }
+
+    Ok(weight)
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn spiel(
+    ctx: PoiseContext<'_>,
+    #[description = "Das Spiel, das angezeigt werden soll"]
+    #[autocomplete = "any_game_autocomplete"]
+    game: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let short = game
+        .split('\'')
+        .nth(1)
+        .ok_or("Game could not be parsed!")?
+        .to_string();
+
+    let d = ctx.data().read().await;
+    let game = d
+        .games
+        .iter()
+        .find(|g| g.short == short)
+        .ok_or("Kürzel gehört zu keinem Spiel")?;
+    let team = |iso: &str| {
+        d.teams
+            .iter()
+            .find(|t| t.iso == iso)
+            .map(|t| format!("{} {}", t.name, t.flag))
+            .unwrap_or(iso.to_string())
+    };
+    let bets = d.bets.get(&short).cloned().unwrap_or_default();
+
+    let mut lines = vec![
+        format!(
+            "# {}: {} vs {}",
+            game.name,
+            team(&game.team1_iso),
+            team(&game.team2_iso)
+        ),
+        format!(
+            "**Anstoß:** {} ({})",
+            game.start_time.format("%d.%m.%Y %H:%M"),
+            game.matchday_name()
+        ),
+        format!("**Modifier:** x{}", game.modifier),
+        format!(
+            "**Ergebnis:** {}",
+            game.result
+                .as_ref()
+                .map(|r| format!("{}:{}", r.0, r.1))
+                .unwrap_or("-".to_string())
+        ),
+    ];
+
+    if game.start_time > get_now() {
+        lines.push(format!(
+            "{} Tipps abgegeben, sie werden bei Anpfiff aufgedeckt.",
+            bets.len()
+        ));
+        if let Some(bet) = bets.iter().find(|b| b.user == ctx.author().id) {
+            lines.push(format!("Dein Tipp: {}", format_tip(bet)));
+        }
+        ctx.send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content(lines.join("\n")),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if !bets.is_empty() {
+        lines.push("## Verteilung".to_string());
+        for outcome in [Outcome::Home, Outcome::Draw, Outcome::Away] {
+            let count = bets
+                .iter()
+                .filter(|b| Outcome::of(b.team1, b.team2) == outcome)
+                .count();
+            lines.push(format!(
+                "{}: {count} ({:.0}%)",
+                outcome.name(),
+                count as f64 * 100.0 / bets.len() as f64
+            ));
+        }
+        let scores = bets
+            .iter()
+            .map(|b| (b.team1, b.team2))
+            .counts()
+            .into_iter()
+            .sorted_by_key(|(s, c)| (std::cmp::Reverse(*c), *s))
+            .take(3)
+            .map(|((t1, t2), c)| format!("{t1}:{t2} ({c}x)"))
+            .join(", ");
+        lines.push(format!("Häufigste Tipps: {scores}"));
+    }
+
+    let mut table = AsciiTable::default();
+    table.column(0).set_header("Spieler");
+    table.column(1).set_header("Tipp");
+    table.column(2).set_header("Punkte");
+
+    let mut data: Vec<(Option<u32>, String, String)> = Vec::new();
+    for bet in &bets {
+        let name = bet
+            .user
+            .to_user(ctx.http())
+            .await
+            .map(|u| u.name)
+            .unwrap_or("UNKNOWN".to_string());
+        data.push((bet_points(&d, game, bet), name, format_tip(bet)));
+    }
+    let data = data
+        .into_iter()
+        .sorted_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)))
+        .map(|(points, name, tip)| {
+            vec![
+                name,
+                tip,
+                points.map(|p| p.to_string()).unwrap_or("-".to_string()),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    if bets.is_empty() {
+        lines.push("Niemand hat dieses Spiel getippt.".to_string());
+    } else {
+        lines.push(format!("```\n{}\n```", table.format(data)));
+    }
+
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content(lines.join("\n")),
+    )
+    .await?;
+
+    Ok(())
+}
+
 #[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
 async fn get_bets(ctx: PoiseContext<'_>) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
@@ -305,8 +741,8 @@ async fn get_bets(ctx: PoiseContext<'_>) -> Result<(), Error> {
 
     let global_bets = d
         .global_bets
-        .iter()
-        .filter_map(|(k, v)| {
+        .values()
+        .filter_map(|v| {
             v.bets
                 .iter()
                 .find(|a| a.0 == user)
@@ -325,20 +761,10 @@ async fn get_bets(ctx: PoiseContext<'_>) -> Result<(), Error> {
         global_bets_data.push(vec![
             global_bet.name.clone(),
             global_bet.points.to_string(),
-            d.teams
-                .iter()
-                .find(|t| t.iso == bet.1)
-                .map(|t| t.name.clone())
-                .unwrap_or("ERROR".to_string()),
+            team_names(&d.teams, &bet.1),
             global_bet
                 .result
-                .map(|r| {
-                    d.teams
-                        .iter()
-                        .find(|t| t.iso == r)
-                        .map(|t| t.name.clone())
-                        .unwrap_or("ERROR".to_string())
-                })
+                .map(|r| team_names(&d.teams, &r))
                 .unwrap_or("-".to_string()),
         ])
     }
@@ -350,7 +776,7 @@ async fn get_bets(ctx: PoiseContext<'_>) -> Result<(), Error> {
     )))
     .await?;
 
-    let mut bets = d
+    let bets = d
         .bets
         .iter()
         .filter_map(|(k, v)| {
@@ -398,7 +824,7 @@ async fn get_bets(ctx: PoiseContext<'_>) -> Result<(), Error> {
                 .find(|t| t.iso == game.team2_iso)
                 .map(|t| t.name.clone())
                 .unwrap_or("ERROR".to_string()),
-            format!(" {}:{}", bet.team1, bet.team2),
+            format_tip(bet),
             game.result
                 .map(|r| format!("{}:{} {}", r.0, r.1, r.2))
                 .unwrap_or("-:-".to_string()),
@@ -425,11 +851,217 @@ async fn get_bets(ctx: PoiseContext<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+fn virtual_row(name: &str, tally: Tally) -> Vec<String> {
+    vec![
+        "-".to_string(),
+        name.to_string(),
+        tally.points.to_string(),
+        String::new(),
+    ]
+}
+
+/// The points table of `users`, or of everybody if no filter is given.
+async fn points_table_string(
+    ctx: PoiseContext<'_>,
+    d: &DataInter,
+    users: Option<&HashSet<UserId>>,
+) -> String {
+    let tallies = filtered(&user_tallies(d), users);
+    let snapshots = timeline(d);
+    let previous = previous_tallies(&snapshots).map(|p| ranks(d, &filtered(p, users)));
+
+    let mut points_table = AsciiTable::default();
+    // points_table.set_max_width(70);
+    points_table.column(0).set_header("#");
+    points_table.column(1).set_header("Spieler");
+    points_table.column(2).set_header("Punkte");
+    points_table.column(3).set_header("±");
+
+    let mut virtuals = if d.settings.virtual_participants {
+        crate::virtuals::virtual_tallies(d)
+    } else {
+        Vec::new()
+    };
+    virtuals.sort_by_key(|(_, t)| std::cmp::Reverse(t.points));
+    let mut virtuals = virtuals.into_iter().peekable();
+
+    let mut points_table_data: Vec<Vec<String>> = Vec::new();
+    for standing in standings(d, &tallies) {
+        while let Some((name, tally)) = virtuals.next_if(|(_, t)| t.points > standing.tally.points)
+        {
+            points_table_data.push(virtual_row(name, tally));
+        }
+        let name = standing
+            .user
+            .to_user(ctx.http())
+            .await
+            .map(|u| u.name.clone())
+            .unwrap_or("UNKNOWN".to_string());
+        let arrow = movement(
+            previous
+                .as_ref()
+                .and_then(|p| p.get(&standing.user).copied()),
+            standing.rank,
+        );
+        points_table_data.push(vec![
+            format!("{}.", standing.rank),
+            name,
+            standing.tally.points.to_string(),
+            arrow,
+        ]);
+    }
+    for (name, tally) in virtuals {
+        points_table_data.push(virtual_row(name, tally));
+    }
+
+    points_table.format(points_table_data)
+}
+
+async fn resolve_league(
+    ctx: PoiseContext<'_>,
+    d: &DataInter,
+    liga: Option<&String>,
+) -> Result<Option<HashSet<UserId>>, Error> {
+    let Some(name) = liga else {
+        return Ok(None);
+    };
+    let league = d.leagues.get(name).ok_or("Liga existiert nicht!")?;
+    Ok(Some(league_members(ctx, league, participants(d)).await))
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn rangliste(
+    ctx: PoiseContext<'_>,
+    #[description = "Nur die Mitglieder dieser Liga anzeigen"]
+    #[autocomplete = "league_autocomplete"]
+    liga: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let d = ctx.data().read().await;
+    let members = resolve_league(ctx, &d, liga.as_ref()).await?;
+
+    if members
+        .as_ref()
+        .is_some_and(|m| !m.contains(&ctx.author().id))
+    {
+        ctx.reply("Du bist kein Mitglied dieser Liga!").await?;
+        return Ok(());
+    }
+
+    let points_table_string = points_table_string(ctx, &d, members.as_ref()).await;
+    let leader = leader_line(ctx, &d, members.as_ref()).await;
+    ctx.send(CreateReply::default().ephemeral(true).content(format!(
+        "# Rangliste{}\n{}```\n{points_table_string}\n```",
+        liga.map(|l| format!(" {l}")).unwrap_or_default(),
+        leader.map(|l| format!("{l}\n")).unwrap_or_default()
+    )))
+    .await?;
+
+    Ok(())
+}
+
+async fn pending_game_autocomplete<'a>(
+    ctx: PoiseContext<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = String> + 'a {
+    let d = ctx.data.read().await;
+    let gs = d
+        .games
+        .iter()
+        .filter(|g| g.result.is_none() && g.start_time <= get_now())
+        .sorted_by_key(|g| g.start_time)
+        .cloned()
+        .collect::<Vec<_>>();
+    serenity::futures::stream::iter(gs)
+        .filter(move |n: &Game| serenity::futures::future::ready(n.short.starts_with(partial)))
+        .map(|g| {
+            format!(
+                "{} {} vs {} '{}'",
+                g.name, g.team1_iso, g.team2_iso, g.short
+            )
+        })
+}
+
+fn parse_score(score: &str) -> Option<(u16, u16)> {
+    let (team1, team2) = score.split_once(':')?;
+    Some((team1.trim().parse().ok()?, team2.trim().parse().ok()?))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn was_waere_wenn(
+    ctx: PoiseContext<'_>,
+    #[description = "Ein angepfiffenes Spiel ohne Ergebnis"]
+    #[autocomplete = "pending_game_autocomplete"]
+    spiel1: String,
+    #[description = "Angenommenes Ergebnis, z.B. 3:0"] ergebnis1: String,
+    #[description = "Ein weiteres angepfiffenes Spiel"]
+    #[autocomplete = "pending_game_autocomplete"]
+    spiel2: Option<String>,
+    #[description = "Angenommenes Ergebnis für Spiel 2"] ergebnis2: Option<String>,
+    #[description = "Ein weiteres angepfiffenes Spiel"]
+    #[autocomplete = "pending_game_autocomplete"]
+    spiel3: Option<String>,
+    #[description = "Angenommenes Ergebnis für Spiel 3"] ergebnis3: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    // Works on a copy, the real data is never touched.
+    let mut d = ctx.data().read().await.clone();
+
+    let mut assumed = Vec::new();
+    for (game, score) in [
+        (Some(spiel1), Some(ergebnis1)),
+        (spiel2, ergebnis2),
+        (spiel3, ergebnis3),
+    ] {
+        let (game, score) = match (game, score) {
+            (Some(game), Some(score)) => (game, score),
+            (None, None) => continue,
+            _ => return Err("Zu jedem Spiel gehört ein Ergebnis!".into()),
+        };
+        let short = game
+            .split('\'')
+            .nth(1)
+            .ok_or("Game could not be parsed!")?
+            .to_string();
+        let (team1, team2) = parse_score(&score).ok_or(format!("Ungültiges Ergebnis: {score}"))?;
+
+        let g = d
+            .games
+            .iter_mut()
+            .find(|g| g.short == short)
+            .ok_or("Kürzel gehört zu keinem Spiel")?;
+        if g.result.is_some() {
+            return Err(format!("{} ist bereits beendet!", g.name).into());
+        }
+        // Tips stay hidden until kickoff, assumed results would reveal them.
+        if g.start_time > get_now() {
+            return Err(format!("{} hat noch nicht begonnen!", g.name).into());
+        }
+        g.result = Some((team1, team2, String::new()));
+        assumed.push(format!("{} {team1}:{team2}", g.name));
+    }
+
+    let points_table_string = points_table_string(ctx, &d, None).await;
+    ctx.send(CreateReply::default().ephemeral(true).content(format!(
+        "# Was wäre wenn…\n{}\n```\n{points_table_string}\n```",
+        assumed.join(", ")
+    )))
+    .await?;
+
+    Ok(())
+}
+
 #[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
 async fn print_overview(
     ctx: PoiseContext<'_>,
     channel: Option<Channel>,
     clear: Option<bool>,
+    #[description = "Nur die Mitglieder dieser Liga anzeigen"]
+    #[autocomplete = "league_autocomplete"]
+    liga: Option<String>,
 ) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
@@ -449,78 +1081,34 @@ async fn print_overview(
     }
 
     let d = ctx.data().lock().await;
+    let members = resolve_league(ctx, &d, liga.as_ref()).await?;
 
     // START OVERVIEW
 
-    let mut user_bets_points = HashMap::new();
-    for (game_short, bets) in d.bets.iter() {
-        let g = d.games.iter().find(|g| &g.short == game_short).unwrap();
-        let result = if let Some(r) = g.result.as_ref() {
-            r
-        } else {
-            for bet in bets {
-                user_bets_points.entry(bet.user).or_insert(0u32);
-            }
-
-            continue;
-        };
-
-        for bet in bets {
-            let points = if bet.team1 == result.0 && bet.team2 == result.1 {
-                POINTS_CORRECT
-            } else if (bet.team1 as i16 - bet.team2 as i16) == (result.0 as i16 - result.1 as i16) {
-                POINTS_TENDENZ
-            } else if (bet.team1 > bet.team2 && result.0 > result.1)
-                || (bet.team1 < bet.team2 && result.0 < result.1)
-            {
-                POINTS_TEAM
-            } else {
-                0
-            };
-
-            let ps = user_bets_points.entry(bet.user).or_insert(0u32);
-            *ps += points * g.modifier;
-        }
-    }
-
-    for (_, b) in d.global_bets.iter() {
-        if let Some(res) = &b.result {
-            for (user, tip) in b.bets.iter() {
-                if tip == res {
-                    let ps = user_bets_points.entry(*user).or_insert(0u32);
-                    *ps += b.points as u32;
-                }
-            }
-        }
-    }
-
-    let mut points_table = AsciiTable::default();
-    // points_table.set_max_width(70);
-    points_table.column(0).set_header("Spieler");
-    points_table.column(1).set_header("Punkte");
-
-    let mut points_table_data: Vec<Vec<String>> = Vec::new();
-    for (k, v) in user_bets_points
-        .iter()
-        .sorted_by(|a, b| a.1.cmp(&b.1).reverse())
-    {
-        let name = k
-            .to_user(ctx.http())
-            .await
-            .map(|u| u.name.clone())
-            .unwrap_or("UNKNOWN".to_string());
-        points_table_data.push(vec![name, v.to_string()]);
-    }
-
-    let points_table_string = points_table.format(points_table_data);
+    let points_table_string = points_table_string(ctx, &d, members.as_ref()).await;
+    let leader = leader_line(ctx, &d, members.as_ref()).await;
 
     channel
         .send_message(
             &ctx,
-            CreateMessage::new().content(format!("# Übersicht\n```\n{points_table_string}\n```")),
+            CreateMessage::new().content(format!(
+                "# Übersicht{}\n{}```\n{points_table_string}\n```",
+                liga.map(|l| format!(" {l}")).unwrap_or_default(),
+                leader.map(|l| format!("{l}\n")).unwrap_or_default()
+            )),
         )
         .await?;
 
+    if !d.squads.is_empty() {
+        let squad_table_string = crate::squads::squad_table_string(&d);
+        channel
+            .send_message(
+                &ctx,
+                CreateMessage::new().content(format!("# Squads\n```\n{squad_table_string}\n```")),
+            )
+            .await?;
+    }
+
     // END OVERVIEW
 
     // START GLOBAL BETS
@@ -532,14 +1120,14 @@ async fn print_overview(
     global_bets_table.column(3).set_header("Gewinner");
 
     let mut global_bets = d.global_bets.values().cloned().collect::<Vec<_>>();
-    global_bets.sort_by(|b1, b2| b1.start_time.cmp(&b2.start_time));
+    global_bets.sort_by_key(|b| b.start_time);
 
     let mut global_bets_data: Vec<Vec<String>> = Vec::new();
 
     for bet in global_bets {
         let winner = bet
             .result
-            .map(|w| d.teams.iter().find(|c| c.iso == w).unwrap().name.clone())
+            .map(|w| team_names(&d.teams, &w))
             .unwrap_or("_".to_string());
         global_bets_data.push(vec![
             bet.name,
@@ -574,9 +1162,9 @@ async fn print_overview(
     games_table.column(5).set_header("M");
     games_table.column(6).set_header("Ergebnis");
 
-    let mut games = d.games.iter().cloned().collect::<Vec<_>>();
+    let mut games = d.games.to_vec();
 
-    games.sort_by(|g1, g2| g1.start_time.cmp(&g2.start_time));
+    games.sort_by_key(|g| g.start_time);
 
     let mut games_table_data: Vec<Vec<String>> = Vec::new();
     for game in games {
@@ -621,18 +1209,22 @@ async fn print_overview(
         .cloned()
         .collect::<Vec<_>>();
 
-    games.sort_by(|g1, g2| g1.start_time.cmp(&g2.start_time));
+    games.sort_by_key(|g| g.start_time);
 
     let users = d
         .bets
-        .iter()
-        .map(|(_, v)| v.iter().map(|b| b.user).collect::<Vec<_>>())
-        .flatten()
+        .values()
+        .flat_map(|v| v.iter().map(|b| b.user))
         .unique()
+        .filter(|u| members.as_ref().is_none_or(|m| m.contains(u)))
         .collect::<Vec<_>>();
 
     channel
-        .send_message(&ctx, CreateMessage::new().content(format!("# Tipps")))
+        .send_message(
+            &ctx,
+            CreateMessage::new()
+                .content("# Tipps\n-# * = Joker (doppelte Punkte), xN = Vertrauensgewicht"),
+        )
         .await?;
 
     for chunk in games.chunks(10) {
@@ -652,8 +1244,7 @@ async fn print_overview(
                 let bets = d.bets.get(&g.short);
                 let tip = if let Some(bets) = bets {
                     let bet = bets.iter().find(|b| &b.user == user);
-                    bet.map(|b| format!("{}:{}", b.team1, b.team2))
-                        .unwrap_or("-:-".to_string())
+                    bet.map(format_tip).unwrap_or("-:-".to_string())
                 } else {
                     "-:-".to_string()
                 };
@@ -685,6 +1276,10 @@ async fn add_global_bet(
     short: String,
     points: u16,
     start_time_string: String,
+    #[description = "Anzahl Tipps pro Spieler"]
+    #[min = 1]
+    picks: Option<u16>,
+    #[description = "Punkte pro richtigem Tipp statt alles oder nichts"] partial: Option<bool>,
 ) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
@@ -698,7 +1293,10 @@ async fn add_global_bet(
             short,
             points,
             start_time,
+            picks: picks.unwrap_or(crate::data::default_picks()),
+            partial: partial.unwrap_or(false),
             result: None,
+            resolved_at: None,
             bets: Vec::new(),
         },
     );
@@ -720,10 +1318,16 @@ async fn global_bet_autocomplete<'a>(
                 g.short.starts_with(partial) && g.start_time > get_now(),
             )
         })
-        .map(|(_, g)| format!("{} ({}pts) '{}'", g.name, g.points, g.short))
+        .map(|(_, g)| {
+            if g.picks > 1 {
+                format!("{} ({}pts, {} Tipps) '{}'", g.name, g.points, g.picks, g.short)
+            } else {
+                format!("{} ({}pts) '{}'", g.name, g.points, g.short)
+            }
+        })
 }
 
-async fn country_autocomplete<'a>(
+pub async fn country_autocomplete<'a>(
     ctx: PoiseContext<'_>,
     partial: &'a str,
 ) -> impl Stream<Item = String> + 'a {
@@ -768,13 +1372,35 @@ async fn bet_global(
         return Ok(());
     }
 
-    if let Some((_, b)) = bet.bets.iter_mut().find(|b| b.0 == user) {
-        *b = iso;
+    let picks = bet.picks as usize;
+    let tip = if let Some((_, b)) = bet.bets.iter_mut().find(|b| b.0 == user) {
+        b
+    } else {
+        bet.bets.push((user, Vec::new()));
+        &mut bet.bets.last_mut().unwrap().1
+    };
+
+    if picks <= 1 {
+        *tip = vec![iso];
+    } else if let Some(i) = tip.iter().position(|t| *t == iso) {
+        tip.remove(i);
+    } else if tip.len() >= picks {
+        ctx.reply(format!(
+            "Es sind nur {picks} Tipps erlaubt! Entferne zuerst einen Tipp, indem du das Land erneut auswählst."
+        ))
+        .await?;
+        return Ok(());
     } else {
-        bet.bets.push((user, iso));
+        tip.push(iso);
     }
 
-    ctx.reply("Bet saved!").await?;
+    let tip = tip.clone();
+    ctx.reply(format!(
+        "Bet saved! ({}/{picks}) {}",
+        tip.len(),
+        team_names(&d.teams, &tip)
+    ))
+    .await?;
 
     Ok(())
 }
@@ -785,7 +1411,7 @@ async fn add_global_score(
     #[description = "The Bet you wanna add the score to"]
     #[autocomplete = "global_bet_autocomplete"]
     global_bet: String,
-    #[description = "The country that won"]
+    #[description = "A correct answer (selecting it again removes it)"]
     #[autocomplete = "country_autocomplete"]
     country: String,
 ) -> Result<(), Error> {
@@ -801,14 +1427,139 @@ async fn add_global_score(
         .find(|c| c.iso == iso)
         .ok_or("Country not valid")?;
 
+    let before = leaderboard_entries(&d);
     let bet = d
         .global_bets
         .get_mut(&bet_ident)
         .ok_or("Global Bet not valid")?;
 
-    bet.result = Some(iso);
+    let result = bet.result.get_or_insert_with(Vec::new);
+    if let Some(i) = result.iter().position(|r| *r == iso) {
+        result.remove(i);
+    } else {
+        result.push(iso);
+    }
+    if result.is_empty() {
+        bet.result = None;
+        bet.resolved_at = None;
+    } else {
+        bet.resolved_at = Some(get_now());
+    }
+
+    let name = bet.name.clone();
+    let result = bet.result.clone().unwrap_or_default();
+    if !result.is_empty() {
+        webhooks::emit(
+            &d,
+            Event::GlobalBetResolved {
+                name,
+                result: result.clone(),
+            },
+        );
+    }
+    webhooks::emit_leaderboard(&d, before);
+    ctx.reply(format!(
+        "Score was updated! Richtige Antworten: {}",
+        team_names(&d.teams, &result)
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn set_jokers(
+    ctx: PoiseContext<'_>,
+    #[description = "Anzahl Joker pro Spieler"] jokers: u32,
+    #[description = "Joker gelten pro Spieltag statt für das ganze Turnier"]
+    per_matchday: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let mut d = ctx.data().lock().await;
+    d.settings.jokers = jokers;
+    d.settings.jokers_per_matchday = per_matchday.unwrap_or(false);
+
+    ctx.reply(if d.settings.jokers_per_matchday {
+        format!("Jeder Spieler hat jetzt {jokers} Joker pro Spieltag.")
+    } else {
+        format!("Jeder Spieler hat jetzt {jokers} Joker.")
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn set_confidence(
+    ctx: PoiseContext<'_>,
+    #[description = "Punkte mit dem Vertrauensgewicht des Tipps multiplizieren"] enabled: bool,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let mut d = ctx.data().lock().await;
+    d.settings.confidence = enabled;
+
+    ctx.reply(if enabled {
+        "Vertrauensmodus aktiviert: Pro Spieltag werden die Gewichte 1..N auf die Spiele verteilt."
+    } else {
+        "Vertrauensmodus deaktiviert."
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn set_rarity(
+    ctx: PoiseContext<'_>,
+    #[description = "Punkte für seltene Tendenzen erhöhen"] enabled: bool,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let mut d = ctx.data().lock().await;
+    d.settings.rarity = enabled;
+
+    ctx.reply(if enabled {
+        "Seltenheitswertung aktiviert: Punkte × (2·Tipps − Tipps mit richtiger Tendenz) / Tipps."
+    } else {
+        "Seltenheitswertung deaktiviert."
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn set_tiebreakers(
+    ctx: PoiseContext<'_>,
+    #[description = "Reihenfolge bei Punktgleichheit, z.B. \"exakt,tendenz,global,frage\""]
+    reihenfolge: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let tiebreakers = reihenfolge
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| TieBreaker::from_name(t).ok_or(format!("Unbekanntes Kriterium: {t}")))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unique()
+        .collect::<Vec<_>>();
+
+    let mut d = ctx.data().lock().await;
+    let reply = if tiebreakers.is_empty() {
+        "Bei Punktgleichheit teilen sich die Spieler den Platz.".to_string()
+    } else {
+        format!(
+            "Bei Punktgleichheit entscheidet: {}",
+            tiebreakers.iter().map(|t| t.name()).join(" → ")
+        )
+    };
+    d.settings.tiebreakers = Some(tiebreakers);
 
-    ctx.reply("Score was added!").await?;
+    ctx.reply(reply).await?;
 
     Ok(())
 }
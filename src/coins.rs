@@ -0,0 +1,318 @@
+use ascii_table::AsciiTable;
+use itertools::Itertools;
+use poise::{ChoiceParameter, Command, CreateReply};
+use serenity::all::UserId;
+
+use crate::cmds::{game_autocomplete, get_now};
+use crate::data::{Data, DataInter, LedgerEntry, LedgerKind, Outcome, Stake};
+use crate::{Error, PoiseContext, START_COINS};
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![einsatz(), coins(), set_start_coins()]
+}
+
+pub fn balance(d: &DataInter, user: UserId) -> i64 {
+    let start = d.settings.start_coins.unwrap_or(START_COINS) as i64;
+    start
+        + d.ledger
+            .iter()
+            .filter(|e| e.user == user)
+            .map(|e| e.amount)
+            .sum::<i64>()
+}
+
+/// Pays out the pot of a game to everyone who staked on the right outcome,
+/// proportional to their stake. Coins lost to rounding go to the largest
+/// winning stake. If nobody was right all stakes are refunded.
+/// Earlier payouts for the game are reverted first, so correcting a result
+/// just settles again.
+pub fn settle(d: &mut DataInter, short: &str) {
+    d.ledger
+        .retain(|e| !(e.game == short && e.kind != LedgerKind::Stake));
+
+    let Some(result) = d
+        .games
+        .iter()
+        .find(|g| g.short == short)
+        .and_then(|g| g.result.clone())
+    else {
+        return;
+    };
+    let Some(stakes) = d.stakes.get(short) else {
+        return;
+    };
+
+    let outcome = Outcome::of(result.0, result.1);
+    let pot = stakes.iter().map(|s| s.coins).sum::<u64>();
+    let winning = stakes
+        .iter()
+        .filter(|s| s.outcome == outcome)
+        .map(|s| s.coins)
+        .sum::<u64>();
+
+    let mut entries = stakes
+        .iter()
+        .filter_map(|s| {
+            if winning == 0 {
+                Some((s.user, LedgerKind::Refund, s.coins))
+            } else if s.outcome == outcome {
+                let share = pot as u128 * s.coins as u128 / winning as u128;
+                Some((s.user, LedgerKind::Payout, share as u64))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if winning > 0 {
+        let paid = entries.iter().map(|e| e.2).sum::<u64>();
+        if let Some(largest) = entries
+            .iter()
+            .zip(stakes.iter().filter(|s| s.outcome == outcome))
+            .position_max_by_key(|(_, s)| s.coins)
+        {
+            entries[largest].2 += pot - paid;
+        }
+    }
+
+    let entries = entries
+        .into_iter()
+        .map(|(user, kind, amount)| LedgerEntry {
+            user,
+            game: short.to_string(),
+            kind,
+            amount: amount as i64,
+        })
+        .collect::<Vec<_>>();
+
+    d.ledger.extend(entries);
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn einsatz(
+    ctx: PoiseContext<'_>,
+    #[description = "Das Spiel, auf das du Coins setzen willst"]
+    #[autocomplete = "game_autocomplete"]
+    game: String,
+    #[description = "Der Ausgang, auf den du setzt"] outcome: Outcome,
+    #[description = "Anzahl Coins (0 zieht den Einsatz zurück)"]
+    #[min = 0]
+    coins: u64,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut d = ctx.data().lock().await;
+
+    let user = ctx.author().id;
+
+    let game_tag = game
+        .split('\'')
+        .nth(1)
+        .ok_or("Game could not be parsed!")?
+        .to_string();
+    let real_game = d
+        .games
+        .iter()
+        .find(|e| e.short == game_tag)
+        .ok_or("Game does not exist!")?
+        .clone();
+
+    if real_game.start_time <= get_now() {
+        ctx.reply("Der Einsatz für dieses Spiel kann nicht mehr verändert werden!")
+            .await?;
+        return Ok(());
+    }
+
+    let previous = d
+        .stakes
+        .get(&real_game.short)
+        .and_then(|s| s.iter().find(|s| s.user == user))
+        .map(|s| s.coins)
+        .unwrap_or(0);
+    let available = balance(&d, user) + previous as i64;
+    if coins as i64 > available {
+        ctx.reply(format!("Du hast nur {available} Coins zur Verfügung!"))
+            .await?;
+        return Ok(());
+    }
+
+    d.ledger
+        .retain(|e| !(e.user == user && e.game == real_game.short && e.kind == LedgerKind::Stake));
+    let stakes = d.stakes.entry(real_game.short.clone()).or_default();
+    stakes.retain(|s| s.user != user);
+
+    if coins == 0 {
+        ctx.reply(format!(
+            "Einsatz für {} zurückgezogen. Guthaben: {} Coins",
+            real_game.name,
+            balance(&d, user)
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    stakes.push(Stake {
+        user,
+        outcome,
+        coins,
+    });
+    d.ledger.push(LedgerEntry {
+        user,
+        game: real_game.short.clone(),
+        kind: LedgerKind::Stake,
+        amount: -(coins as i64),
+    });
+
+    ctx.reply(format!(
+        "Einsatz gespeichert: {coins} Coins auf {} in {} {} vs {}. Guthaben: {} Coins",
+        outcome.name(),
+        real_game.name,
+        real_game.team1_iso,
+        real_game.team2_iso,
+        balance(&d, user)
+    ))
+    .await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn coins(ctx: PoiseContext<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let d = ctx.data().read().await;
+
+    let mut table = AsciiTable::default();
+    table.column(0).set_header("#");
+    table.column(1).set_header("Spieler");
+    table.column(2).set_header("Coins");
+    table.column(3).set_header("Im Spiel");
+
+    let users = d
+        .ledger
+        .iter()
+        .map(|e| e.user)
+        .unique()
+        .map(|u| {
+            let open = d
+                .stakes
+                .iter()
+                .filter(|(short, _)| {
+                    d.games
+                        .iter()
+                        .any(|g| &g.short == *short && g.result.is_none())
+                })
+                .flat_map(|(_, s)| s.iter().filter(|s| s.user == u))
+                .map(|s| s.coins)
+                .sum::<u64>();
+            (u, balance(&d, u), open)
+        })
+        .sorted_by_key(|(u, b, _)| (std::cmp::Reverse(*b), *u))
+        .collect::<Vec<_>>();
+
+    let mut data: Vec<Vec<String>> = Vec::new();
+    for (user, balance, open) in users.iter().copied() {
+        let rank = 1 + users.iter().filter(|(_, b, _)| *b > balance).count();
+        let name = user
+            .to_user(ctx.http())
+            .await
+            .map(|u| u.name)
+            .unwrap_or("UNKNOWN".to_string());
+        data.push(vec![
+            format!("{rank}."),
+            name,
+            balance.to_string(),
+            open.to_string(),
+        ]);
+    }
+
+    let table_string = table.format(data);
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content(format!("# Coins\n```\n{table_string}\n```")),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn set_start_coins(
+    ctx: PoiseContext<'_>,
+    #[description = "Startguthaben jedes Spielers"] coins: u64,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let mut d = ctx.data().lock().await;
+    d.settings.start_coins = Some(coins);
+
+    ctx.reply(format!("Jeder Spieler startet jetzt mit {coins} Coins."))
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::game;
+
+    fn stake(user: u64, outcome: Outcome, coins: u64) -> Stake {
+        Stake {
+            user: UserId::new(user),
+            outcome,
+            coins,
+        }
+    }
+
+    fn payouts(d: &DataInter) -> Vec<(u64, i64)> {
+        d.ledger
+            .iter()
+            .filter(|e| e.kind == LedgerKind::Payout)
+            .map(|e| (e.user.get(), e.amount))
+            .collect()
+    }
+
+    fn settled(stakes: Vec<Stake>) -> DataInter {
+        let mut d = DataInter {
+            games: vec![crate::data::Game {
+                result: Some((2, 0, String::new())),
+                ..game("A1")
+            }],
+            ..Default::default()
+        };
+        d.stakes.insert("A1".to_string(), stakes);
+        settle(&mut d, "A1");
+        d
+    }
+
+    #[test]
+    fn rounding_leftovers_go_to_the_largest_stake() {
+        let d = settled(vec![
+            stake(1, Outcome::Home, 10),
+            stake(2, Outcome::Home, 20),
+            stake(3, Outcome::Home, 10),
+            stake(4, Outcome::Away, 60),
+        ]);
+
+        // 100 * 10 / 40 = 25, 100 * 20 / 40 = 50: nothing lost.
+        assert_eq!(payouts(&d), [(1, 25), (2, 50), (3, 25)]);
+
+        let d = settled(vec![
+            stake(1, Outcome::Home, 1),
+            stake(2, Outcome::Home, 2),
+            stake(3, Outcome::Draw, 4),
+        ]);
+        // 7 * 1 / 3 = 2, 7 * 2 / 3 = 4, the remaining coin goes to user 2.
+        assert_eq!(payouts(&d), [(1, 2), (2, 5)]);
+    }
+
+    #[test]
+    fn large_stakes_do_not_overflow() {
+        let coins = 1 << 40;
+        let d = settled(vec![
+            stake(1, Outcome::Home, coins),
+            stake(2, Outcome::Away, coins),
+        ]);
+        assert_eq!(payouts(&d), [(1, 2 * coins as i64)]);
+    }
+}
@@ -2,12 +2,12 @@ use std::collections::HashMap;
 use std::env;
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
-use chrono::Utc;
 
 use poise::futures_util::lock::{Mutex, MutexGuard};
-use serde::{Deserialize, Serialize};
-use serenity::all::UserId;
-use serenity::model::Timestamp;
+use serde::{Deserialize, Deserializer, Serialize};
+use serenity::all::{RoleId, UserId};
+
+use crate::Error;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Team {
@@ -20,6 +20,10 @@ pub fn default_modifier() -> u32 {
     1
 }
 
+pub fn default_picks() -> u16 {
+    1
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Game {
     pub name: String,
@@ -30,14 +34,114 @@ pub struct Game {
     pub result: Option<(u16, u16, String)>,
     #[serde(default = "default_modifier")]
     pub modifier: u32,
+    #[serde(default)]
+    pub matchday: Option<String>,
+    /// Id of the match at the results provider.
+    #[serde(default)]
+    pub external_id: Option<String>,
+}
+
+impl Game {
+    /// Games without an explicit matchday are grouped by their kickoff date.
+    pub fn matchday_name(&self) -> String {
+        self.matchday
+            .clone()
+            .unwrap_or_else(|| self.start_time.format("%d.%m.%Y").to_string())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct DataInter {
     pub teams: Vec<Team>,
     pub games: Vec<Game>,
     pub bets: HashMap<String, Vec<Bet>>,
-    pub global_bets: HashMap<String, GlobalBet>
+    pub global_bets: HashMap<String, GlobalBet>,
+    #[serde(default)]
+    pub settings: Settings,
+    #[serde(default)]
+    pub survivor: Vec<SurvivorPick>,
+    #[serde(default)]
+    pub stakes: HashMap<String, Vec<Stake>>,
+    #[serde(default)]
+    pub ledger: Vec<LedgerEntry>,
+    #[serde(default)]
+    pub duels: Vec<Duel>,
+    #[serde(default)]
+    pub leagues: HashMap<String, League>,
+    #[serde(default)]
+    pub squads: HashMap<String, Squad>,
+    #[serde(default)]
+    pub goal_guesses: HashMap<UserId, u32>,
+    /// Last known Discord name of everyone who used a command, for places
+    /// without access to Discord like the HTTP API.
+    #[serde(default)]
+    pub names: HashMap<UserId, String>,
+    #[serde(default)]
+    pub pending_results: Vec<PendingResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct Settings {
+    #[serde(default)]
+    pub jokers: u32,
+    #[serde(default)]
+    pub jokers_per_matchday: bool,
+    #[serde(default)]
+    pub confidence: bool,
+    #[serde(default)]
+    pub rarity: bool,
+    #[serde(default)]
+    pub start_coins: Option<u64>,
+    #[serde(default)]
+    pub squad_scoring: SquadScoring,
+    #[serde(default)]
+    pub tiebreakers: Option<Vec<TieBreaker>>,
+    #[serde(default)]
+    pub virtual_participants: bool,
+    #[serde(default)]
+    pub webhooks: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TieBreaker {
+    Exact,
+    Tendency,
+    Global,
+    Question,
+}
+
+impl TieBreaker {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TieBreaker::Exact => "exakt",
+            TieBreaker::Tendency => "tendenz",
+            TieBreaker::Global => "global",
+            TieBreaker::Question => "frage",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        default_tiebreakers()
+            .into_iter()
+            .find(|t| t.name().eq_ignore_ascii_case(name))
+    }
+}
+
+pub fn default_tiebreakers() -> Vec<TieBreaker> {
+    vec![
+        TieBreaker::Exact,
+        TieBreaker::Tendency,
+        TieBreaker::Global,
+        TieBreaker::Question,
+    ]
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SquadScoring {
+    #[default]
+    Sum,
+    Average,
+    Top(u32),
 }
 
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
@@ -46,17 +150,167 @@ pub struct GlobalBet {
     pub short: String,
     pub points: u16,
     pub start_time: chrono::NaiveDateTime,
-    pub result: Option<String>,
-    pub bets: Vec<(UserId, String)>
+    #[serde(default = "default_picks")]
+    pub picks: u16,
+    #[serde(default)]
+    pub partial: bool,
+    #[serde(deserialize_with = "de_result")]
+    pub result: Option<Vec<String>>,
+    #[serde(default)]
+    pub resolved_at: Option<chrono::NaiveDateTime>,
+    #[serde(deserialize_with = "de_bets")]
+    pub bets: Vec<(UserId, Vec<String>)>
+}
+
+impl GlobalBet {
+    pub fn points_for(&self, tip: &[String]) -> u32 {
+        let Some(result) = &self.result else {
+            return 0;
+        };
+
+        let correct = tip.iter().filter(|t| result.contains(t)).count() as u32;
+        if self.partial {
+            correct * self.points as u32
+        } else if correct == self.picks as u32 {
+            self.points as u32
+        } else {
+            0
+        }
+    }
+}
+
+// Older saves store a single iso per result and per bet.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<OneOrMany> for Vec<String> {
+    fn from(value: OneOrMany) -> Self {
+        match value {
+            OneOrMany::One(s) => vec![s],
+            OneOrMany::Many(v) => v,
+        }
+    }
+}
+
+fn de_result<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<String>>, D::Error> {
+    Ok(Option::<OneOrMany>::deserialize(d)?.map(Into::into))
+}
+
+fn de_bets<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<(UserId, Vec<String>)>, D::Error> {
+    Ok(Vec::<(UserId, OneOrMany)>::deserialize(d)?
+        .into_iter()
+        .map(|(u, t)| (u, t.into()))
+        .collect())
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Bet {
     pub user: UserId,
     pub team1: u16,
-    pub team2: u16
+    pub team2: u16,
+    #[serde(default)]
+    pub joker: bool,
+    #[serde(default)]
+    pub weight: Option<u32>,
+}
+
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, poise::ChoiceParameter,
+)]
+pub enum Outcome {
+    #[name = "Heimsieg"]
+    Home,
+    #[name = "Unentschieden"]
+    Draw,
+    #[name = "Auswärtssieg"]
+    Away,
+}
+
+impl Outcome {
+    pub fn of(team1: u16, team2: u16) -> Self {
+        match team1.cmp(&team2) {
+            std::cmp::Ordering::Greater => Outcome::Home,
+            std::cmp::Ordering::Equal => Outcome::Draw,
+            std::cmp::Ordering::Less => Outcome::Away,
+        }
+    }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SurvivorPick {
+    pub user: UserId,
+    pub matchday: String,
+    pub game: String,
+    pub team_iso: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Stake {
+    pub user: UserId,
+    pub outcome: Outcome,
+    pub coins: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerKind {
+    Stake,
+    Refund,
+    Payout,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LedgerEntry {
+    pub user: UserId,
+    pub game: String,
+    pub kind: LedgerKind,
+    pub amount: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum DuelTarget {
+    Game(String),
+    Matchday(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Duel {
+    pub challenger: UserId,
+    pub opponent: UserId,
+    pub target: DuelTarget,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct League {
+    pub name: String,
+    pub code: String,
+    pub owner: UserId,
+    pub members: Vec<UserId>,
+    pub role: Option<RoleId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Squad {
+    pub name: String,
+    pub members: Vec<UserId>,
+}
+
+/// A score fetched from a results provider, waiting for an admin to confirm it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PendingResult {
+    pub short: String,
+    pub team1: u16,
+    pub team2: u16,
+    pub source: String,
+    /// Rejected scores stay here so they are not proposed again.
+    #[serde(default)]
+    pub rejected: bool,
+}
+
+#[derive(Clone)]
 pub struct Data {
     inter: Arc<Mutex<DataInter>>,
 }
@@ -76,23 +330,33 @@ impl<'a> DerefMut for SaveGuard<'a> {
     }
 }
 
+/// Read-only access that does not write the save file when dropped.
+pub struct ReadGuard<'a>(MutexGuard<'a, DataInter>);
+
+impl Deref for ReadGuard<'_> {
+    type Target = DataInter;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 impl Drop for SaveGuard<'_> {
     fn drop(&mut self) {
         println!("Save!!!");
-        let d: &DataInter = &self;
-        let dat = serde_json::to_string(d).unwrap();
-        if let Ok(f) = env::var("SAVE_FILE") {
-            std::fs::write(f, dat).unwrap();
-        }
+        save_data(self);
     }
 }
 
 impl Data {
-    pub async fn lock(&self) -> SaveGuard {
+    pub async fn lock(&self) -> SaveGuard<'_> {
         let g = self.inter.lock().await;
         SaveGuard(g)
     }
 
+    pub async fn read(&self) -> ReadGuard<'_> {
+        ReadGuard(self.inter.lock().await)
+    }
+
     pub fn new() -> Self {
         Self {
             inter: Arc::new(Mutex::new(load_data()))
@@ -100,16 +364,17 @@ impl Data {
     }
 }
 
-fn load_data() -> DataInter {
+impl Default for Data {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn load_data() -> DataInter {
     let p = env::var("SAVE_FILE").unwrap_or("".to_string());
 
     if p.is_empty() {
-        return DataInter {
-            games: vec![],
-            teams: vec![],
-            bets: HashMap::new(),
-            global_bets: HashMap::new()
-        }
+        return DataInter::default();
     }
 
     let f = std::fs::read_to_string(p);
@@ -117,11 +382,105 @@ fn load_data() -> DataInter {
         serde_json::from_str(&f).unwrap()
     } else {
         println!("Loading file failed");
-        DataInter {
-            games: vec![],
-            teams: vec![],
-            bets: HashMap::new(),
-            global_bets: HashMap::new()
+        DataInter::default()
+    }
+}
+
+pub fn save_data(d: &DataInter) {
+    if let Ok(f) = env::var("SAVE_FILE") {
+        write_save_file(&f, d).unwrap();
+    }
+}
+
+/// Like `load_data`, but reports a missing or broken file instead of starting empty.
+pub fn read_save_file(path: &str) -> Result<DataInter, Error> {
+    let f = std::fs::read_to_string(path).map_err(|why| format!("Reading {path} failed: {why}"))?;
+    Ok(serde_json::from_str(&f).map_err(|why| format!("Parsing {path} failed: {why}"))?)
+}
+
+pub fn write_save_file(path: &str, d: &DataInter) -> Result<(), Error> {
+    let dat = serde_json::to_string(d)?;
+    std::fs::write(path, dat).map_err(|why| format!("Writing {path} failed: {why}"))?;
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn isos(isos: &[&str]) -> Vec<String> {
+        isos.iter().map(|i| i.to_string()).collect()
+    }
+
+    fn semi_finalists(partial: bool) -> GlobalBet {
+        GlobalBet {
+            name: "Halbfinalisten".to_string(),
+            short: "HF".to_string(),
+            points: 3,
+            start_time: NaiveDate::from_ymd_opt(2024, 6, 14)
+                .unwrap()
+                .and_hms_opt(21, 0, 0)
+                .unwrap(),
+            picks: 4,
+            partial,
+            result: Some(isos(&["ESP", "FRA", "ENG", "NLD"])),
+            resolved_at: None,
+            bets: Vec::new(),
         }
     }
+
+    #[test]
+    fn partial_counts_every_correct_pick() {
+        let bet = semi_finalists(true);
+        assert_eq!(bet.points_for(&isos(&["ESP", "FRA", "DEU", "PRT"])), 6);
+        assert_eq!(bet.points_for(&isos(&["ESP"])), 3);
+        assert_eq!(bet.points_for(&[]), 0);
+    }
+
+    #[test]
+    fn all_or_nothing_needs_every_pick() {
+        let bet = semi_finalists(false);
+        assert_eq!(bet.points_for(&isos(&["ESP", "FRA", "ENG", "NLD"])), 3);
+        assert_eq!(bet.points_for(&isos(&["ESP"])), 0);
+        assert_eq!(bet.points_for(&isos(&["ESP", "FRA", "ENG"])), 0);
+        assert_eq!(bet.points_for(&isos(&["ESP", "FRA", "ENG", "DEU"])), 0);
+    }
+
+    #[test]
+    fn unresolved_bets_give_no_points() {
+        let mut bet = semi_finalists(true);
+        bet.result = None;
+        assert_eq!(bet.points_for(&isos(&["ESP"])), 0);
+    }
+
+    #[test]
+    fn legacy_saves_store_single_isos() {
+        let bet: GlobalBet = serde_json::from_str(
+            r#"{"name": "Europameister", "short": "WIN", "points": 10,
+                "start_time": "2024-06-14T21:00:00", "result": "ESP",
+                "bets": [["421669444205412372", "ESP"], ["1", "DEU"]]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(bet.picks, 1);
+        assert!(!bet.partial);
+        assert_eq!(bet.result, Some(isos(&["ESP"])));
+        assert_eq!(
+            bet.bets,
+            [
+                (UserId::new(421669444205412372), isos(&["ESP"])),
+                (UserId::new(1), isos(&["DEU"]))
+            ]
+        );
+        assert_eq!(bet.points_for(&bet.bets[0].1), 10);
+        assert_eq!(bet.points_for(&bet.bets[1].1), 0);
+
+        let open: GlobalBet = serde_json::from_str(
+            r#"{"name": "Europameister", "short": "WIN", "points": 10,
+                "start_time": "2024-06-14T21:00:00", "result": null, "bets": []}"#,
+        )
+        .unwrap();
+        assert_eq!(open.result, None);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,321 @@
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use ascii_table::AsciiTable;
+use chrono::NaiveDateTime;
+use itertools::Itertools;
+use poise::futures_util::StreamExt;
+use poise::{Command, CreateReply};
+use serenity::all::{
+    ButtonStyle, ComponentInteractionCollector, CreateActionRow, CreateButton,
+    CreateInteractionResponse, CreateInteractionResponseMessage, User, UserId,
+};
+use serenity::futures::Stream;
+
+use crate::cmds::{game_autocomplete, get_now};
+use crate::data::{Data, DataInter, Duel, DuelTarget, Game};
+use crate::scoring::bet_points;
+use crate::{Error, PoiseContext};
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![duell(), rivalitaet()]
+}
+
+fn target_games<'a>(d: &'a DataInter, target: &DuelTarget) -> Vec<&'a Game> {
+    d.games
+        .iter()
+        .filter(|g| match target {
+            DuelTarget::Game(short) => &g.short == short,
+            DuelTarget::Matchday(matchday) => &g.matchday_name() == matchday,
+        })
+        .collect()
+}
+
+fn kickoff(d: &DataInter, target: &DuelTarget) -> Option<NaiveDateTime> {
+    target_games(d, target).iter().map(|g| g.start_time).min()
+}
+
+fn describe(d: &DataInter, target: &DuelTarget) -> String {
+    match target {
+        DuelTarget::Game(short) => d
+            .games
+            .iter()
+            .find(|g| &g.short == short)
+            .map(|g| format!("{} {} vs {}", g.name, g.team1_iso, g.team2_iso))
+            .unwrap_or(short.clone()),
+        DuelTarget::Matchday(matchday) => format!("Spieltag {matchday}"),
+    }
+}
+
+/// Points of both duellists from their normal bets, once every game of the
+/// duel has a result.
+pub fn duel_points(d: &DataInter, duel: &Duel) -> Option<(u32, u32)> {
+    let games = target_games(d, &duel.target);
+    if games.is_empty() || games.iter().any(|g| g.result.is_none()) {
+        return None;
+    }
+
+    let points = |user: UserId| {
+        games
+            .iter()
+            .filter_map(|g| {
+                let bet = d.bets.get(&g.short)?.iter().find(|b| b.user == user)?;
+                bet_points(d, g, bet)
+            })
+            .sum::<u32>()
+    };
+    Some((points(duel.challenger), points(duel.opponent)))
+}
+
+async fn matchday_autocomplete<'a>(
+    ctx: PoiseContext<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = String> + 'a {
+    let d = ctx.data.read().await;
+    let matchdays = d
+        .games
+        .iter()
+        .sorted_by_key(|g| g.start_time)
+        .map(|g| g.matchday_name())
+        .unique()
+        .filter(|m| kickoff(&d, &DuelTarget::Matchday(m.clone())).is_some_and(|k| k > get_now()))
+        .collect::<Vec<_>>();
+    serenity::futures::stream::iter(matchdays)
+        .filter(move |m| serenity::futures::future::ready(m.starts_with(partial)))
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn duell(
+    ctx: PoiseContext<'_>,
+    #[description = "Dein Gegner"] gegner: User,
+    #[description = "Ein einzelnes Spiel"]
+    #[autocomplete = "game_autocomplete"]
+    spiel: Option<String>,
+    #[description = "Ein ganzer Spieltag"]
+    #[autocomplete = "matchday_autocomplete"]
+    spieltag: Option<String>,
+) -> Result<(), Error> {
+    let challenger = ctx.author().id;
+
+    if gegner.id == challenger || gegner.bot {
+        ctx.send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content("Du kannst nur andere Spieler herausfordern!"),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let target = match (spiel, spieltag) {
+        (Some(game), None) => DuelTarget::Game(
+            game.split('\'')
+                .nth(1)
+                .ok_or("Game could not be parsed!")?
+                .to_string(),
+        ),
+        (None, Some(matchday)) => DuelTarget::Matchday(matchday),
+        _ => {
+            ctx.send(
+                CreateReply::default()
+                    .ephemeral(true)
+                    .content("Wähle entweder ein Spiel oder einen Spieltag!"),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    // The lock must not be held while waiting for the opponent.
+    let (start, description) = {
+        let d = ctx.data().read().await;
+        let start = kickoff(&d, &target).ok_or("Spiel oder Spieltag existiert nicht!")?;
+        (start, describe(&d, &target))
+    };
+
+    if start <= get_now() {
+        ctx.send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content("Das Duell muss vor dem Anpfiff angenommen werden!"),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let accept = format!("{}-duell-annehmen", ctx.id());
+    let decline = format!("{}-duell-ablehnen", ctx.id());
+    let reply = ctx
+        .send(
+            CreateReply::default()
+                .content(format!(
+                    "⚔️ <@{}>, <@{challenger}> fordert dich zum Duell heraus: {description}",
+                    gegner.id
+                ))
+                .components(vec![CreateActionRow::Buttons(vec![
+                    CreateButton::new(&accept)
+                        .label("Annehmen")
+                        .style(ButtonStyle::Success),
+                    CreateButton::new(&decline)
+                        .label("Ablehnen")
+                        .style(ButtonStyle::Danger),
+                ])]),
+        )
+        .await?;
+    let msg = reply.message().await?;
+
+    let timeout = (start - get_now())
+        .to_std()
+        .unwrap_or_default()
+        .min(Duration::from_secs(24 * 60 * 60));
+    let Some(mci) = ComponentInteractionCollector::new(ctx.serenity_context())
+        .message_id(msg.id)
+        .author_id(gegner.id)
+        .timeout(timeout)
+        .await
+    else {
+        reply
+            .edit(
+                poise::Context::Application(ctx),
+                CreateReply::default()
+                    .content(format!(
+                        "⚔️ Das Duell zwischen <@{challenger}> und <@{}> ({description}) wurde nicht angenommen.",
+                        gegner.id
+                    ))
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let content = if mci.data.custom_id == accept {
+        let mut d = ctx.data().lock().await;
+        if kickoff(&d, &target).is_some_and(|k| k <= get_now()) {
+            format!("⚔️ Zu spät, {description} hat bereits begonnen.")
+        } else {
+            d.duels.push(Duel {
+                challenger,
+                opponent: gegner.id,
+                target,
+            });
+            format!(
+                "⚔️ <@{}> hat das Duell gegen <@{challenger}> angenommen: {description}",
+                gegner.id
+            )
+        }
+    } else {
+        format!(
+            "⚔️ <@{}> hat das Duell gegen <@{challenger}> abgelehnt.",
+            gegner.id
+        )
+    };
+
+    mci.create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(vec![]),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn rivalitaet(
+    ctx: PoiseContext<'_>,
+    #[description = "Nur Duelle gegen diesen Spieler anzeigen"] gegner: Option<User>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let d = ctx.data().read().await;
+    let user = ctx.author().id;
+
+    // (opponent, target, own points, opponent points)
+    let duels = d
+        .duels
+        .iter()
+        .filter_map(|duel| {
+            let points = duel_points(&d, duel);
+            if duel.challenger == user {
+                Some((duel.opponent, &duel.target, points))
+            } else if duel.opponent == user {
+                Some((duel.challenger, &duel.target, points.map(|(a, b)| (b, a))))
+            } else {
+                None
+            }
+        })
+        .filter(|(o, _, _)| gegner.as_ref().is_none_or(|g| g.id == *o))
+        .collect::<Vec<_>>();
+
+    let name = |id: UserId| async move {
+        id.to_user(ctx.http())
+            .await
+            .map(|u| u.name)
+            .unwrap_or("UNKNOWN".to_string())
+    };
+
+    let mut table = AsciiTable::default();
+    let mut data: Vec<Vec<String>> = Vec::new();
+
+    if let Some(gegner) = &gegner {
+        table.column(0).set_header("Duell");
+        table.column(1).set_header("Du");
+        table.column(2).set_header(gegner.name.clone());
+        table.column(3).set_header("Ergebnis");
+
+        for (_, target, points) in &duels {
+            let (own, other, result) = match points {
+                Some((own, other)) => (
+                    own.to_string(),
+                    other.to_string(),
+                    match own.cmp(other) {
+                        Ordering::Greater => "Sieg",
+                        Ordering::Equal => "Unentschieden",
+                        Ordering::Less => "Niederlage",
+                    },
+                ),
+                None => ("-".to_string(), "-".to_string(), "offen"),
+            };
+            data.push(vec![describe(&d, target), own, other, result.to_string()]);
+        }
+    } else {
+        table.column(0).set_header("Gegner");
+        table.column(1).set_header("S");
+        table.column(2).set_header("U");
+        table.column(3).set_header("N");
+        table.column(4).set_header("Offen");
+
+        let mut records: Vec<(UserId, [u32; 4])> = Vec::new();
+        for (opponent, _, points) in duels.iter().sorted_by_key(|(o, _, _)| *o) {
+            if records.last().is_none_or(|(o, _)| o != opponent) {
+                records.push((*opponent, [0; 4]));
+            }
+            let i = match points.map(|(a, b)| a.cmp(&b)) {
+                Some(Ordering::Greater) => 0,
+                Some(Ordering::Equal) => 1,
+                Some(Ordering::Less) => 2,
+                None => 3,
+            };
+            records.last_mut().unwrap().1[i] += 1;
+        }
+
+        for (opponent, record) in records {
+            let mut row = vec![name(opponent).await];
+            row.extend(record.iter().map(|c| c.to_string()));
+            data.push(row);
+        }
+    }
+
+    let table_string = table.format(data);
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content(format!("# Rivalitäten\n```\n{table_string}\n```")),
+    )
+    .await?;
+
+    Ok(())
+}
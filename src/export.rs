@@ -0,0 +1,207 @@
+use std::path::Path;
+
+use itertools::Itertools;
+use poise::{Command, CreateReply};
+use serenity::all::{CreateAttachment, UserId};
+
+use crate::data::{Data, DataInter};
+use crate::scoring::{bet_points, standings, user_tallies};
+use crate::{Error, PoiseContext};
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![export()]
+}
+
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Starts with a byte order mark so spreadsheet programs pick up UTF-8.
+fn csv(header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut csv = String::from("\u{feff}");
+    csv.push_str(&header.join(","));
+    csv.push_str("\r\n");
+    for row in rows {
+        csv.push_str(&row.iter().map(|v| field(v)).join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+fn name(d: &DataInter, user: UserId) -> String {
+    d.names.get(&user).cloned().unwrap_or_default()
+}
+
+/// All tournament data as `(file name, content)` pairs.
+pub fn csv_files(d: &DataInter) -> Vec<(String, String)> {
+    let teams = d
+        .teams
+        .iter()
+        .map(|t| vec![t.iso.clone(), t.name.clone(), t.flag.clone()])
+        .collect();
+
+    let games = d
+        .games
+        .iter()
+        .sorted_by_key(|g| g.start_time)
+        .map(|g| {
+            vec![
+                g.short.clone(),
+                g.name.clone(),
+                g.matchday_name(),
+                g.team1_iso.clone(),
+                g.team2_iso.clone(),
+                g.start_time.format("%Y-%m-%d %H:%M").to_string(),
+                g.modifier.to_string(),
+                g.result
+                    .as_ref()
+                    .map(|r| r.0.to_string())
+                    .unwrap_or_default(),
+                g.result
+                    .as_ref()
+                    .map(|r| r.1.to_string())
+                    .unwrap_or_default(),
+                g.result.as_ref().map(|r| r.2.clone()).unwrap_or_default(),
+            ]
+        })
+        .collect();
+
+    let bets = d
+        .games
+        .iter()
+        .sorted_by_key(|g| g.start_time)
+        .flat_map(|g| {
+            d.bets
+                .get(&g.short)
+                .into_iter()
+                .flatten()
+                .map(move |b| (g, b))
+        })
+        .map(|(g, b)| {
+            vec![
+                g.short.clone(),
+                b.user.to_string(),
+                name(d, b.user),
+                b.team1.to_string(),
+                b.team2.to_string(),
+                b.joker.to_string(),
+                b.weight.map(|w| w.to_string()).unwrap_or_default(),
+                bet_points(d, g, b)
+                    .map(|p| p.to_string())
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+
+    let global_bets = d
+        .global_bets
+        .values()
+        .sorted_by_key(|b| b.start_time)
+        .flat_map(|b| {
+            let bet = vec![
+                b.short.clone(),
+                b.name.clone(),
+                b.points.to_string(),
+                b.result.as_ref().map(|r| r.join(" ")).unwrap_or_default(),
+            ];
+            b.bets.iter().map(move |(user, tip)| {
+                let mut row = bet.clone();
+                row.extend([
+                    user.to_string(),
+                    name(d, *user),
+                    tip.join(" "),
+                    b.points_for(tip).to_string(),
+                ]);
+                row
+            })
+        })
+        .collect();
+
+    let points = standings(d, &user_tallies(d))
+        .into_iter()
+        .map(|s| {
+            vec![
+                s.rank.to_string(),
+                s.user.to_string(),
+                name(d, s.user),
+                s.tally.points.to_string(),
+                s.tally.exact.to_string(),
+                s.tally.tendency.to_string(),
+                s.tally.global.to_string(),
+            ]
+        })
+        .collect();
+
+    vec![
+        (
+            "teams.csv".to_string(),
+            csv(&["iso", "name", "flag"], teams),
+        ),
+        (
+            "games.csv".to_string(),
+            csv(
+                &[
+                    "short", "name", "matchday", "team1", "team2", "kickoff", "modifier", "goals1",
+                    "goals2", "note",
+                ],
+                games,
+            ),
+        ),
+        (
+            "bets.csv".to_string(),
+            csv(
+                &[
+                    "game", "user", "name", "goals1", "goals2", "joker", "weight", "points",
+                ],
+                bets,
+            ),
+        ),
+        (
+            "global_bets.csv".to_string(),
+            csv(
+                &[
+                    "bet", "title", "value", "result", "user", "name", "tip", "points",
+                ],
+                global_bets,
+            ),
+        ),
+        (
+            "points.csv".to_string(),
+            csv(
+                &[
+                    "rank", "user", "name", "points", "exact", "tendency", "global",
+                ],
+                points,
+            ),
+        ),
+    ]
+}
+
+pub fn write_csv_files(d: &DataInter, dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for (file, content) in csv_files(d) {
+        std::fs::write(dir.join(file), content)?;
+    }
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn export(ctx: PoiseContext<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let files = csv_files(&*ctx.data().read().await);
+
+    let mut reply = CreateReply::default()
+        .ephemeral(true)
+        .content("Export aller Daten als CSV:");
+    for (file, content) in files {
+        reply = reply.attachment(CreateAttachment::bytes(content.into_bytes(), file));
+    }
+    ctx.send(reply).await?;
+
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet};
+
+use ascii_table::AsciiTable;
+use itertools::Itertools;
+use poise::{Command, CreateReply};
+use serenity::all::{User, UserId};
+
+use crate::data::{Data, DataInter};
+use crate::scoring::{ranks, timeline, Snapshot, Tally};
+use crate::{Error, PoiseContext};
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![verlauf()]
+}
+
+pub fn filtered(
+    tallies: &HashMap<UserId, Tally>,
+    users: Option<&HashSet<UserId>>,
+) -> HashMap<UserId, Tally> {
+    tallies
+        .iter()
+        .filter(|(u, _)| users.is_none_or(|m| m.contains(u)))
+        .map(|(u, p)| (*u, *p))
+        .collect()
+}
+
+/// Tallies at the end of the matchday before the most recent one.
+pub fn previous_tallies(snapshots: &[Snapshot]) -> Option<&HashMap<UserId, Tally>> {
+    let current = &snapshots.last()?.matchday;
+    snapshots
+        .iter()
+        .rev()
+        .find(|s| &s.matchday != current)
+        .map(|s| &s.tallies)
+}
+
+pub fn movement(previous: Option<usize>, now: usize) -> String {
+    match previous {
+        Some(p) if p > now => format!("↑{}", p - now),
+        Some(p) if p < now => format!("↓{}", now - p),
+        Some(_) => "=".to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// The current leaders and the snapshot since which they are in front.
+pub fn leader_since(
+    d: &DataInter,
+    snapshots: &[Snapshot],
+    users: Option<&HashSet<UserId>>,
+) -> Option<(Vec<UserId>, String)> {
+    let last = ranks(d, &filtered(&snapshots.last()?.tallies, users));
+    let leaders = last
+        .iter()
+        .filter(|(_, r)| **r == 1)
+        .map(|(u, _)| *u)
+        .sorted()
+        .collect::<Vec<_>>();
+    if leaders.is_empty() || leaders.len() == last.len() {
+        return None;
+    }
+
+    let since = snapshots
+        .iter()
+        .rev()
+        .take_while(|s| {
+            let ranks = ranks(d, &filtered(&s.tallies, users));
+            leaders.iter().all(|l| ranks.get(l) == Some(&1))
+        })
+        .last()?;
+
+    Some((leaders, since.label.clone()))
+}
+
+pub async fn leader_line(
+    ctx: PoiseContext<'_>,
+    d: &DataInter,
+    users: Option<&HashSet<UserId>>,
+) -> Option<String> {
+    let (leaders, since) = leader_since(d, &timeline(d), users)?;
+
+    let mut names = Vec::new();
+    for leader in leaders {
+        names.push(
+            leader
+                .to_user(ctx.http())
+                .await
+                .map(|u| u.name)
+                .unwrap_or("UNKNOWN".to_string()),
+        );
+    }
+
+    Some(format!("👑 {} führt seit {since}", names.join(" & ")))
+}
+
+fn sparkline(values: &[f64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            if max > min {
+                BARS[((v - min) / (max - min) * 7.0).round() as usize]
+            } else {
+                BARS[3]
+            }
+        })
+        .collect()
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn verlauf(
+    ctx: PoiseContext<'_>,
+    #[description = "Spieler (Standard: du selbst)"] user: Option<User>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user = user.as_ref().unwrap_or_else(|| ctx.author());
+    let d = ctx.data().read().await;
+
+    let snapshots = timeline(&d);
+    let matchdays = snapshots
+        .iter()
+        .rev()
+        .unique_by(|s| s.matchday.clone())
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .filter_map(|s| {
+            let tally = s.tallies.get(&user.id)?;
+            let rank = *ranks(&d, &s.tallies).get(&user.id)?;
+            Some((s.matchday.clone(), tally.points, rank))
+        })
+        .collect::<Vec<_>>();
+
+    if matchdays.is_empty() {
+        ctx.reply(format!("Für {} gibt es noch keinen Verlauf.", user.name))
+            .await?;
+        return Ok(());
+    }
+
+    let points = matchdays.iter().map(|m| m.1 as f64).collect::<Vec<_>>();
+    let ranks = matchdays.iter().map(|m| -(m.2 as f64)).collect::<Vec<_>>();
+    let (first, last) = (matchdays.first().unwrap(), matchdays.last().unwrap());
+
+    let mut table = AsciiTable::default();
+    table.column(0).set_header("Spieltag");
+    table.column(1).set_header("Punkte");
+    table.column(2).set_header("Platz");
+    let data = matchdays
+        .iter()
+        .map(|(m, p, r)| vec![m.clone(), p.to_string(), format!("{r}.")])
+        .collect::<Vec<_>>();
+
+    let mut lines = vec![
+        format!("# Verlauf für {}", user.name),
+        format!("`Punkte {}` ({} → {})", sparkline(&points), first.1, last.1),
+        format!(
+            "`Platz  {}` ({}. → {}.)",
+            sparkline(&ranks),
+            first.2,
+            last.2
+        ),
+    ];
+    if let Some((leaders, since)) = leader_since(&d, &snapshots, None) {
+        if leaders.contains(&user.id) {
+            lines.push(format!("👑 Spitzenreiter seit {since}"));
+        }
+    }
+    lines.push(format!("```\n{}\n```", table.format(data)));
+
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content(lines.join("\n")),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use chrono::{NaiveDateTime, Utc};
+use itertools::Itertools;
+use poise::{Command, CreateReply};
+use serenity::all::CreateAttachment;
+
+use crate::data::{Data, DataInter, Game};
+use crate::{Error, PoiseContext, UTC_OFFSET_HOURS};
+
+const GAME_DURATION_MINUTES: i64 = 120;
+const REMINDER_MINUTES: i64 = 60;
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![kalender()]
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Folds lines longer than 75 bytes as required by RFC 5545.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded
+}
+
+fn utc(time: NaiveDateTime) -> String {
+    (time - chrono::Duration::hours(UTC_OFFSET_HOURS))
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+fn event(d: &DataInter, game: &Game, stamp: &str) -> Vec<String> {
+    let team = |iso: &str| {
+        d.teams
+            .iter()
+            .find(|t| t.iso == iso)
+            .map(|t| format!("{} {}", t.flag, t.name))
+            .unwrap_or(iso.to_string())
+    };
+    let description = match &game.result {
+        Some(r) if r.2.is_empty() => format!("Ergebnis: {}:{}", r.0, r.1),
+        Some(r) => format!("Ergebnis: {}:{}\n{}", r.0, r.1, r.2),
+        None => format!("{}, Tipps bis zum Anpfiff", game.matchday_name()),
+    };
+
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@tippy-tappy", escape(&game.short)),
+        format!("DTSTAMP:{stamp}"),
+        format!("DTSTART:{}", utc(game.start_time)),
+        format!(
+            "DTEND:{}",
+            utc(game.start_time + chrono::Duration::minutes(GAME_DURATION_MINUTES))
+        ),
+        format!(
+            "SUMMARY:{}",
+            escape(&format!(
+                "{}: {} vs {}",
+                game.name,
+                team(&game.team1_iso),
+                team(&game.team2_iso)
+            ))
+        ),
+        format!("DESCRIPTION:{}", escape(&description)),
+        "BEGIN:VALARM".to_string(),
+        "ACTION:DISPLAY".to_string(),
+        format!("TRIGGER:-PT{REMINDER_MINUTES}M"),
+        format!(
+            "DESCRIPTION:{}",
+            escape(&format!("Tippabgabe für {} endet zum Anpfiff", game.name))
+        ),
+        "END:VALARM".to_string(),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+pub fn calendar(d: &DataInter) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let events = d
+        .games
+        .iter()
+        .sorted_by_key(|g| g.start_time)
+        .flat_map(|g| event(d, g, &stamp));
+
+    [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        "PRODID:-//tippy-tappy//Tippspiel//DE",
+        "X-WR-CALNAME:Tippspiel",
+    ]
+    .into_iter()
+    .map(String::from)
+    .chain(events)
+    .chain(["END:VCALENDAR".to_string()])
+    .map(|l| fold(&l) + "\r\n")
+    .collect()
+}
+
+pub async fn calendar_feed(State(data): State<Data>) -> impl IntoResponse {
+    let d = data.read().await;
+    (
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        calendar(&d),
+    )
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn kalender(ctx: PoiseContext<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let ics = calendar(&*ctx.data().read().await);
+
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content("Alle Spiele zum Importieren in deinen Kalender:")
+            .attachment(CreateAttachment::bytes(ics.into_bytes(), "spiele.ics")),
+    )
+    .await?;
+
+    Ok(())
+}
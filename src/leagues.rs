@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use poise::futures_util::StreamExt;
+use poise::Command;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serenity::all::{Role, UserId};
+use serenity::futures::Stream;
+
+use crate::data::{Data, DataInter, League};
+use crate::{Error, PoiseContext};
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![liga_erstellen(), liga_beitreten(), liga_verlassen()]
+}
+
+/// All of `candidates` that belong to the league, either by joining with the
+/// invite code or by having the league's role.
+pub async fn league_members(
+    ctx: PoiseContext<'_>,
+    league: &League,
+    candidates: impl IntoIterator<Item = UserId>,
+) -> HashSet<UserId> {
+    let mut members = league.members.iter().copied().collect::<HashSet<_>>();
+
+    if let (Some(role), Some(guild)) = (league.role, ctx.guild_id()) {
+        for user in candidates {
+            if members.contains(&user) {
+                continue;
+            }
+            if let Ok(member) = guild.member(ctx, user).await {
+                if member.roles.contains(&role) {
+                    members.insert(user);
+                }
+            }
+        }
+    }
+
+    members
+}
+
+/// Everyone who placed a bet of any kind.
+pub fn participants(d: &DataInter) -> Vec<UserId> {
+    d.bets
+        .values()
+        .flat_map(|v| v.iter().map(|b| b.user))
+        .chain(
+            d.global_bets
+                .values()
+                .flat_map(|g| g.bets.iter().map(|b| b.0)),
+        )
+        .unique()
+        .collect()
+}
+
+pub async fn league_autocomplete<'a>(
+    ctx: PoiseContext<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = String> + 'a {
+    let user = ctx.author().id;
+    let roles = ctx
+        .author_member()
+        .await
+        .map(|m| m.roles.clone())
+        .unwrap_or_default();
+
+    let d = ctx.data.read().await;
+    let leagues = d
+        .leagues
+        .values()
+        .filter(|l| l.members.contains(&user) || l.role.is_some_and(|r| roles.contains(&r)))
+        .map(|l| l.name.clone())
+        .sorted()
+        .collect::<Vec<_>>();
+    serenity::futures::stream::iter(leagues)
+        .filter(move |n| serenity::futures::future::ready(n.starts_with(partial)))
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn liga_erstellen(
+    ctx: PoiseContext<'_>,
+    #[description = "Name der Liga"] name: String,
+    #[description = "Mitglieder dieser Rolle gehören automatisch dazu"] rolle: Option<Role>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let mut d = ctx.data().lock().await;
+    if d.leagues.contains_key(&name) {
+        ctx.reply("Eine Liga mit diesem Namen existiert bereits!")
+            .await?;
+        return Ok(());
+    }
+
+    let code = loop {
+        let code = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(6)
+            .map(|c| (c as char).to_ascii_uppercase())
+            .collect::<String>();
+        if d.leagues.values().all(|l| l.code != code) {
+            break code;
+        }
+    };
+
+    d.leagues.insert(
+        name.clone(),
+        League {
+            name: name.clone(),
+            code: code.clone(),
+            owner: ctx.author().id,
+            members: vec![ctx.author().id],
+            role: rolle.as_ref().map(|r| r.id),
+        },
+    );
+
+    ctx.reply(format!(
+        "Liga {name} erstellt! Einladungscode: `{code}`{}",
+        rolle
+            .map(|r| format!(" (alle Mitglieder von {} sind automatisch dabei)", r.name))
+            .unwrap_or_default()
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn liga_beitreten(
+    ctx: PoiseContext<'_>,
+    #[description = "Der Einladungscode der Liga"] code: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user = ctx.author().id;
+    let mut d = ctx.data().lock().await;
+    let league = d
+        .leagues
+        .values_mut()
+        .find(|l| l.code.eq_ignore_ascii_case(code.trim()))
+        .ok_or("Ungültiger Einladungscode!")?;
+
+    if !league.members.contains(&user) {
+        league.members.push(user);
+    }
+
+    ctx.reply(format!("Du bist jetzt Mitglied der Liga {}!", league.name))
+        .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn liga_verlassen(
+    ctx: PoiseContext<'_>,
+    #[description = "Die Liga, die du verlassen willst"]
+    #[autocomplete = "league_autocomplete"]
+    liga: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user = ctx.author().id;
+    let mut d = ctx.data().lock().await;
+    let league = d.leagues.get_mut(&liga).ok_or("Liga existiert nicht!")?;
+
+    league.members.retain(|m| *m != user);
+    let reply = if league.role.is_some() {
+        format!(
+            "Du hast die Liga {liga} verlassen. Solange du die Rolle der Liga hast, zählst du weiterhin dazu."
+        )
+    } else {
+        format!("Du hast die Liga {liga} verlassen.")
+    };
+    if league.members.is_empty() && league.role.is_none() {
+        d.leagues.remove(&liga);
+    }
+
+    ctx.reply(reply).await?;
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use crate::data::Data;
+
+pub mod api;
+mod chances;
+pub mod cmds;
+mod coins;
+pub mod data;
+mod duels;
+pub mod export;
+mod history;
+mod ical;
+mod leagues;
+mod openfootball;
+pub mod results;
+pub mod scoring;
+mod squads;
+mod stats;
+mod survivor;
+#[cfg(test)]
+mod testing;
+mod tiebreak;
+mod virtuals;
+mod web;
+pub mod webhooks;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub type PoiseContext<'a> = poise::ApplicationContext<'a, Data, Error>;
+
+pub const POINTS_CORRECT: u32 = 3;
+pub const POINTS_TENDENZ: u32 = 2;
+pub const POINTS_TEAM: u32 = 1;
+pub const JOKER_FACTOR: u32 = 2;
+pub const START_COINS: u64 = 1000;
+/// Kickoff times are entered in German summer time, see `cmds::get_now`.
+pub const UTC_OFFSET_HOURS: i64 = 2;
@@ -1,28 +1,15 @@
 use std::env;
-use std::ops::{Deref, DerefMut};
 
-use serenity::all::{
-    ActivityData
-    , Ready,
-};
+use serenity::all::{ActivityData, Ready};
 use serenity::async_trait;
 use serenity::prelude::*;
 
-use crate::cmds::get_cmds;
-use crate::data::Data;
-
-mod cmds;
-mod data;
-
-type Error = Box<dyn std::error::Error + Send + Sync>;
-type PoiseContext<'a> = poise::ApplicationContext<'a, Data, Error>;
+use tippy_tappy::cmds::get_cmds;
+use tippy_tappy::data::Data;
+use tippy_tappy::{api, results, webhooks};
 
 struct Handler;
 
-pub const POINTS_CORRECT: u32 = 3;
-pub const POINTS_TENDENZ: u32 = 2;
-pub const POINTS_TEAM: u32 = 1;
-
 #[async_trait]
 impl EventHandler for Handler {
     async fn ready(&self, ctx: Context, data_about_bot: Ready) {
@@ -40,9 +27,27 @@ async fn main() {
         | GatewayIntents::DIRECT_MESSAGES
         | GatewayIntents::MESSAGE_CONTENT;
 
+    let data = Data::new();
+    tokio::spawn(api::serve(data.clone()));
+    tokio::spawn(webhooks::watch_bet_windows(data.clone()));
+    tokio::spawn(results::watch_feed(data.clone()));
+
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
             commands: get_cmds(),
+            pre_command: |ctx| {
+                Box::pin(async move {
+                    let author = ctx.author();
+                    let known = ctx.data().read().await.names.get(&author.id) == Some(&author.name);
+                    if !known {
+                        ctx.data()
+                            .lock()
+                            .await
+                            .names
+                            .insert(author.id, author.name.clone());
+                    }
+                })
+            },
             prefix_options: poise::PrefixFrameworkOptions {
                 prefix: Some("!".to_string()),
                 ..Default::default()
@@ -52,7 +57,7 @@ async fn main() {
         .setup(|ctx, _ready, framework| {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                Ok(Data::new())
+                Ok(data)
             })
         })
         .build();
@@ -0,0 +1,474 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use poise::Command;
+use serde::Deserialize;
+
+use crate::data::{default_modifier, Data, DataInter, Game, Team};
+use crate::{Error, PoiseContext, UTC_OFFSET_HOURS};
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![import_openfootball()]
+}
+
+/// English name, FIFA code, iso, flag code and German name. Flag codes with two
+/// letters are countries, longer ones are subdivisions like `gbsct`.
+const COUNTRIES: &[(&str, &str, &str, &str, &str)] = &[
+    ("Albania", "ALB", "ALB", "AL", "Albanien"),
+    ("Algeria", "ALG", "DZA", "DZ", "Algerien"),
+    ("Argentina", "ARG", "ARG", "AR", "Argentinien"),
+    ("Australia", "AUS", "AUS", "AU", "Australien"),
+    ("Austria", "AUT", "AUT", "AT", "Österreich"),
+    ("Belgium", "BEL", "BEL", "BE", "Belgien"),
+    (
+        "Bosnia and Herzegovina",
+        "BIH",
+        "BIH",
+        "BA",
+        "Bosnien und Herzegowina",
+    ),
+    ("Brazil", "BRA", "BRA", "BR", "Brasilien"),
+    ("Bulgaria", "BUL", "BGR", "BG", "Bulgarien"),
+    ("Cameroon", "CMR", "CMR", "CM", "Kamerun"),
+    ("Canada", "CAN", "CAN", "CA", "Kanada"),
+    ("Chile", "CHI", "CHL", "CL", "Chile"),
+    ("Colombia", "COL", "COL", "CO", "Kolumbien"),
+    ("Costa Rica", "CRC", "CRI", "CR", "Costa Rica"),
+    ("Croatia", "CRO", "HRV", "HR", "Kroatien"),
+    ("Czech Republic", "CZE", "CZE", "CZ", "Tschechien"),
+    ("Czechia", "CZE", "CZE", "CZ", "Tschechien"),
+    ("Denmark", "DEN", "DNK", "DK", "Dänemark"),
+    ("Ecuador", "ECU", "ECU", "EC", "Ecuador"),
+    ("Egypt", "EGY", "EGY", "EG", "Ägypten"),
+    ("England", "ENG", "GB-ENG", "gbeng", "England"),
+    ("Finland", "FIN", "FIN", "FI", "Finnland"),
+    ("France", "FRA", "FRA", "FR", "Frankreich"),
+    ("Georgia", "GEO", "GEO", "GE", "Georgien"),
+    ("Germany", "GER", "DEU", "DE", "Deutschland"),
+    ("Ghana", "GHA", "GHA", "GH", "Ghana"),
+    ("Greece", "GRE", "GRC", "GR", "Griechenland"),
+    ("Hungary", "HUN", "HUN", "HU", "Ungarn"),
+    ("Iceland", "ISL", "ISL", "IS", "Island"),
+    ("Iran", "IRN", "IRN", "IR", "Iran"),
+    ("Italy", "ITA", "ITA", "IT", "Italien"),
+    ("Ivory Coast", "CIV", "CIV", "CI", "Elfenbeinküste"),
+    ("Japan", "JPN", "JPN", "JP", "Japan"),
+    ("Mexico", "MEX", "MEX", "MX", "Mexiko"),
+    ("Morocco", "MAR", "MAR", "MA", "Marokko"),
+    ("Netherlands", "NED", "NLD", "NL", "Niederlande"),
+    ("Nigeria", "NGA", "NGA", "NG", "Nigeria"),
+    ("North Macedonia", "MKD", "MKD", "MK", "Nordmazedonien"),
+    ("Northern Ireland", "NIR", "GB-NIR", "GB", "Nordirland"),
+    ("Norway", "NOR", "NOR", "NO", "Norwegen"),
+    ("Panama", "PAN", "PAN", "PA", "Panama"),
+    ("Paraguay", "PAR", "PRY", "PY", "Paraguay"),
+    ("Peru", "PER", "PER", "PE", "Peru"),
+    ("Poland", "POL", "POL", "PL", "Polen"),
+    ("Portugal", "POR", "PRT", "PT", "Portugal"),
+    ("Qatar", "QAT", "QAT", "QA", "Katar"),
+    ("Republic of Ireland", "IRL", "IRL", "IE", "Irland"),
+    ("Ireland", "IRL", "IRL", "IE", "Irland"),
+    ("Romania", "ROU", "ROU", "RO", "Rumänien"),
+    ("Russia", "RUS", "RUS", "RU", "Russland"),
+    ("Saudi Arabia", "KSA", "SAU", "SA", "Saudi-Arabien"),
+    ("Scotland", "SCO", "GB-SCT", "gbsct", "Schottland"),
+    ("Senegal", "SEN", "SEN", "SN", "Senegal"),
+    ("Serbia", "SRB", "SRB", "RS", "Serbien"),
+    ("Slovakia", "SVK", "SVK", "SK", "Slowakei"),
+    ("Slovenia", "SVN", "SVN", "SI", "Slowenien"),
+    ("South Africa", "RSA", "ZAF", "ZA", "Südafrika"),
+    ("South Korea", "KOR", "KOR", "KR", "Südkorea"),
+    ("Korea Republic", "KOR", "KOR", "KR", "Südkorea"),
+    ("Spain", "ESP", "ESP", "ES", "Spanien"),
+    ("Sweden", "SWE", "SWE", "SE", "Schweden"),
+    ("Switzerland", "SUI", "CHE", "CH", "Schweiz"),
+    ("Tunisia", "TUN", "TUN", "TN", "Tunesien"),
+    ("Turkey", "TUR", "TUR", "TR", "Türkei"),
+    ("Türkiye", "TUR", "TUR", "TR", "Türkei"),
+    ("Ukraine", "UKR", "UKR", "UA", "Ukraine"),
+    ("United States", "USA", "USA", "US", "USA"),
+    ("USA", "USA", "USA", "US", "USA"),
+    ("Uruguay", "URU", "URY", "UY", "Uruguay"),
+    ("Wales", "WAL", "GB-WLS", "gbwls", "Wales"),
+];
+
+#[derive(Deserialize)]
+struct File {
+    #[serde(default)]
+    rounds: Vec<Round>,
+    #[serde(default)]
+    matches: Vec<Match>,
+}
+
+#[derive(Deserialize)]
+struct Round {
+    name: String,
+    matches: Vec<Match>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TeamRef {
+    Name(String),
+    Full { name: String, code: Option<String> },
+}
+
+#[derive(Deserialize)]
+struct Score {
+    ft: Option<(u16, u16)>,
+    et: Option<(u16, u16)>,
+}
+
+#[derive(Deserialize)]
+struct Match {
+    round: Option<String>,
+    num: Option<u32>,
+    date: NaiveDate,
+    time: Option<String>,
+    timezone: Option<String>,
+    team1: TeamRef,
+    team2: TeamRef,
+    group: Option<String>,
+    score: Option<Score>,
+    score1: Option<u16>,
+    score2: Option<u16>,
+    score1et: Option<u16>,
+    score2et: Option<u16>,
+}
+
+impl Match {
+    /// The final score, after extra time if there was one.
+    fn result(&self) -> Option<(u16, u16)> {
+        if let Some(score) = &self.score {
+            return score.et.or(score.ft);
+        }
+        match (self.score1et, self.score2et) {
+            (Some(s1), Some(s2)) => Some((s1, s2)),
+            _ => Some((self.score1?, self.score2?)),
+        }
+    }
+
+    /// Kickoff in the time zone the bot works in, see `get_now`.
+    fn kickoff(&self) -> Result<NaiveDateTime, Error> {
+        let time = self.time.as_deref().unwrap_or("00:00");
+        let (clock, zone) = time.split_once(' ').unwrap_or((time, ""));
+        let zone = if zone.is_empty() {
+            self.timezone.as_deref().unwrap_or("")
+        } else {
+            zone
+        };
+
+        let local = self
+            .date
+            .and_time(NaiveTime::parse_from_str(clock, "%H:%M")?);
+        let Some(offset) = zone.strip_prefix("UTC") else {
+            return Ok(local);
+        };
+        let offset = if offset.is_empty() {
+            0
+        } else {
+            offset.parse::<i64>()?
+        };
+        Ok(local + chrono::Duration::hours(UTC_OFFSET_HOURS - offset))
+    }
+}
+
+fn flag(code: &str) -> String {
+    if code.len() == 2 {
+        code.chars()
+            .map(|c| char::from_u32(0x1F1E6 + (c as u32 - 'A' as u32)).unwrap_or(' '))
+            .collect()
+    } else {
+        std::iter::once('🏴')
+            .chain(
+                code.chars()
+                    .map(|c| char::from_u32(0xE0000 + c as u32).unwrap_or(' ')),
+            )
+            .chain(std::iter::once('\u{E007F}'))
+            .collect()
+    }
+}
+
+/// Knockout games list placeholders like "W49", "1A" or "3A/D/E/F" until the
+/// teams are known.
+fn is_placeholder(name: &str) -> bool {
+    name.contains('/') || name.chars().any(|c| c.is_ascii_digit())
+}
+
+fn team(team: &TeamRef) -> Option<Team> {
+    let (name, code) = match team {
+        TeamRef::Name(name) => (name, None),
+        TeamRef::Full { name, code } => (name, code.as_ref()),
+    };
+    if is_placeholder(name) {
+        return None;
+    }
+
+    let known = COUNTRIES
+        .iter()
+        .find(|c| c.0.eq_ignore_ascii_case(name) || code.is_some_and(|code| c.1 == code));
+    Some(match known {
+        Some((_, _, iso, flag_code, german)) => Team {
+            name: german.to_string(),
+            flag: flag(flag_code),
+            iso: iso.to_string(),
+        },
+        None => Team {
+            name: name.clone(),
+            flag: "🏳️".to_string(),
+            iso: code
+                .cloned()
+                .unwrap_or(name.chars().take(3).collect::<String>().to_uppercase()),
+        },
+    })
+}
+
+fn round_name(round: &str) -> (String, String) {
+    let lower = round.to_lowercase();
+    let (name, short) = if let Some(n) = lower.strip_prefix("matchday ") {
+        return (format!("Spieltag {n}"), format!("S{n}"));
+    } else if lower.contains("32") {
+        ("Sechzehntelfinale", "SF")
+    } else if lower.contains("16") {
+        ("Achtelfinale", "AF")
+    } else if lower.contains("quarter") {
+        ("Viertelfinale", "VF")
+    } else if lower.contains("semi") {
+        ("Halbfinale", "HF")
+    } else if lower.contains("third") || lower.contains("3rd") {
+        ("Spiel um Platz 3", "P3")
+    } else if lower == "final" {
+        // Not "F", that is taken by the games of group F.
+        ("Finale", "FI")
+    } else {
+        return (round.to_string(), round.chars().take(2).collect());
+    };
+    (name.to_string(), short.to_string())
+}
+
+#[derive(Default)]
+pub struct Summary {
+    pub teams: usize,
+    pub games: usize,
+    pub skipped: usize,
+}
+
+/// Adds the teams and games of an openfootball `worldcup.json`/`euro.json`.
+/// Teams and games that already exist are kept as they are. Nothing is added
+/// if any match can not be read.
+pub fn import(d: &mut DataInter, json: &str) -> Result<Summary, Error> {
+    let file: File = serde_json::from_str(json)?;
+    let matches = file
+        .rounds
+        .into_iter()
+        .flat_map(|r| {
+            let name = r.name;
+            r.matches
+                .into_iter()
+                .map(move |m| (m.round.clone().unwrap_or(name.clone()), m))
+        })
+        .chain(
+            file.matches
+                .into_iter()
+                .map(|m| (m.round.clone().unwrap_or_default(), m)),
+        )
+        .collect::<Vec<_>>();
+
+    let mut summary = Summary::default();
+    let mut teams: Vec<Team> = Vec::new();
+    let mut games: Vec<Game> = Vec::new();
+    let mut group_counters: HashMap<String, u32> = HashMap::new();
+    let mut round_counters: HashMap<String, u32> = HashMap::new();
+    for (round, m) in matches {
+        let (round, round_short) = round_name(&round);
+        let (name, short) = match &m.group {
+            Some(group) => {
+                let letter = group.trim_start_matches("Group ").to_string();
+                let n = group_counters.entry(letter.clone()).or_default();
+                *n += 1;
+                (format!("Gruppe {letter} Spiel {n}"), format!("{letter}{n}"))
+            }
+            None => {
+                let n = round_counters.entry(round_short.clone()).or_default();
+                *n += 1;
+                (format!("{round} {n}"), format!("{round_short}{n}"))
+            }
+        };
+
+        let start_time = m.kickoff()?;
+
+        let (Some(team1), Some(team2)) = (team(&m.team1), team(&m.team2)) else {
+            summary.skipped += 1;
+            continue;
+        };
+        if d.games.iter().chain(&games).any(|g| g.short == short) {
+            summary.skipped += 1;
+            continue;
+        }
+
+        for team in [&team1, &team2] {
+            if !d.teams.iter().chain(&teams).any(|t| t.iso == team.iso) {
+                teams.push(team.clone());
+            }
+        }
+
+        games.push(Game {
+            name,
+            short,
+            team1_iso: team1.iso,
+            team2_iso: team2.iso,
+            start_time,
+            result: m.result().map(|(s1, s2)| (s1, s2, String::new())),
+            modifier: default_modifier(),
+            matchday: Some(round),
+            external_id: m.num.map(|n| n.to_string()),
+        });
+    }
+
+    summary.teams = teams.len();
+    summary.games = games.len();
+    d.teams.extend(teams);
+    d.games.extend(games);
+
+    Ok(summary)
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn import_openfootball(
+    ctx: PoiseContext<'_>,
+    #[description = "Pfad zur worldcup.json/euro.json auf dem Server"] path: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let json = std::fs::read_to_string(&path)?;
+    let mut d = ctx.data().lock().await;
+    let summary = import(&mut d, &json)?;
+
+    ctx.reply(format!(
+        "{} Teams und {} Spiele importiert, {} Spiele übersprungen (bereits vorhanden oder Teams noch offen).",
+        summary.teams, summary.games, summary.skipped
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn time(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 6, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn game<'a>(d: &'a DataInter, short: &str) -> &'a Game {
+        d.games.iter().find(|g| g.short == short).unwrap()
+    }
+
+    /// Newer files: flat match list, `score` objects and the zone in `time`.
+    const EURO: &str = r#"{"name": "Euro 2024", "matches": [
+        {"round": "Matchday 1", "num": 1, "date": "2024-06-14", "time": "21:00 UTC+2",
+         "team1": "Germany", "team2": "Scotland", "group": "Group A",
+         "score": {"ft": [5, 1]}},
+        {"round": "Matchday 1", "num": 2, "date": "2024-06-15", "time": "15:00 UTC+2",
+         "team1": "Hungary", "team2": "Switzerland", "group": "Group A"},
+        {"round": "Matchday 2", "num": 3, "date": "2024-06-16", "time": "18:00 UTC+2",
+         "team1": "Spain", "team2": "Croatia", "group": "Group F"},
+        {"round": "Round of 16", "num": 4, "date": "2024-06-29", "time": "21:00 UTC+2",
+         "team1": "Spain", "team2": "Germany", "score": {"ft": [1, 1], "et": [2, 1]}},
+        {"round": "Round of 16", "num": 5, "date": "2024-06-30", "time": "21:00 UTC+2",
+         "team1": "W39", "team2": "3A/D/E/F"},
+        {"round": "Final", "num": 6, "date": "2024-07-14", "time": "21:00 UTC+2",
+         "team1": "Spain", "team2": "England"}
+    ]}"#;
+
+    /// Older files: rounds, `score1`/`score2` and a separate `timezone`.
+    const WORLDCUP: &str = r#"{"name": "World Cup 2018", "rounds": [
+        {"name": "Matchday 1", "matches": [
+            {"num": 1, "date": "2018-06-14", "time": "18:00", "timezone": "UTC+3",
+             "team1": {"name": "Russia", "code": "RUS"},
+             "team2": {"name": "Saudi Arabia", "code": "KSA"},
+             "group": "Group A", "score1": 5, "score2": 0}
+        ]},
+        {"name": "Round of 16", "matches": [
+            {"num": 2, "date": "2018-07-01", "time": "17:00", "timezone": "UTC+3",
+             "team1": {"name": "Spain", "code": "ESP"},
+             "team2": {"name": "Russia", "code": "RUS"},
+             "score1": 1, "score2": 1, "score1et": 1, "score2et": 1}
+        ]}
+    ]}"#;
+
+    #[test]
+    fn imports_groups_and_knockout_rounds() {
+        let mut d = DataInter::default();
+        let summary = import(&mut d, EURO).unwrap();
+
+        assert_eq!((summary.teams, summary.games, summary.skipped), (7, 5, 1));
+        let shorts = d.games.iter().map(|g| g.short.as_str()).collect::<Vec<_>>();
+        assert_eq!(shorts, ["A1", "A2", "F1", "AF1", "FI1"]);
+        assert_eq!(game(&d, "F1").name, "Gruppe F Spiel 1");
+        assert_eq!(game(&d, "FI1").name, "Finale 1");
+        assert_eq!(game(&d, "FI1").matchday.as_deref(), Some("Finale"));
+        assert_eq!(game(&d, "A1").team2_iso, "GB-SCT");
+        assert_eq!(game(&d, "A1").external_id.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn converts_kickoff_to_bot_time() {
+        let mut d = DataInter::default();
+        import(&mut d, EURO).unwrap();
+        assert_eq!(game(&d, "A1").start_time, time(14, 21, 0));
+
+        let mut d = DataInter::default();
+        import(&mut d, WORLDCUP).unwrap();
+        assert_eq!(
+            game(&d, "A1").start_time,
+            NaiveDate::from_ymd_opt(2018, 6, 14)
+                .unwrap()
+                .and_hms_opt(17, 0, 0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn reads_both_score_formats() {
+        let mut d = DataInter::default();
+        import(&mut d, EURO).unwrap();
+        assert_eq!(game(&d, "A1").result, Some((5, 1, String::new())));
+        assert_eq!(game(&d, "A2").result, None);
+        // After extra time.
+        assert_eq!(game(&d, "AF1").result, Some((2, 1, String::new())));
+
+        let mut d = DataInter::default();
+        import(&mut d, WORLDCUP).unwrap();
+        assert_eq!(game(&d, "A1").result, Some((5, 0, String::new())));
+        assert_eq!(game(&d, "AF1").result, Some((1, 1, String::new())));
+    }
+
+    #[test]
+    fn keeps_existing_games_and_adds_nothing_on_errors() {
+        let mut d = DataInter::default();
+        import(&mut d, EURO).unwrap();
+        d.games[0].name = "Eröffnungsspiel".to_string();
+
+        let summary = import(&mut d, EURO).unwrap();
+        assert_eq!((summary.teams, summary.games, summary.skipped), (0, 0, 6));
+        assert_eq!(d.games[0].name, "Eröffnungsspiel");
+
+        // The first match is fine, the second one can not be read.
+        let broken = r#"{"name": "Euro 2024", "matches": [
+            {"date": "2024-06-14", "time": "21:00 UTC+2", "team1": "Germany",
+             "team2": "Scotland", "group": "Group A"},
+            {"date": "2024-06-15", "time": "15:00 UTC+x", "team1": "Hungary",
+             "team2": "Switzerland", "group": "Group A"}
+        ]}"#;
+        let mut d = DataInter::default();
+        assert!(import(&mut d, broken).is_err());
+        assert!(d.games.is_empty() && d.teams.is_empty());
+    }
+}
@@ -0,0 +1,420 @@
+use std::env;
+use std::time::Duration;
+
+use itertools::Itertools;
+use poise::futures_util::StreamExt;
+use poise::{Command, CreateReply};
+use serde::Deserialize;
+use serenity::async_trait;
+use serenity::futures::Stream;
+
+use crate::cmds::{enter_result, has_news, result_announcement};
+use crate::data::{Data, DataInter, PendingResult};
+use crate::{Error, PoiseContext};
+
+const DEFAULT_POLL_SECONDS: u64 = 300;
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![
+        set_external_id(),
+        pending_results(),
+        confirm_result(),
+        reject_result(),
+    ]
+}
+
+/// A finished match as reported by a provider.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ExternalResult {
+    pub id: String,
+    pub team1: u16,
+    pub team2: u16,
+    #[serde(default = "finished_default")]
+    pub finished: bool,
+}
+
+fn finished_default() -> bool {
+    true
+}
+
+#[async_trait]
+pub trait ResultsProvider: Send + Sync {
+    fn name(&self) -> String;
+
+    async fn fetch(&self) -> Result<Vec<ExternalResult>, Error>;
+}
+
+/// Reads a JSON array of `{"id": "...", "team1": 2, "team2": 1, "finished": true}`
+/// from a URL.
+pub struct JsonFeed {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl JsonFeed {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl ResultsProvider for JsonFeed {
+    fn name(&self) -> String {
+        self.url.clone()
+    }
+
+    async fn fetch(&self) -> Result<Vec<ExternalResult>, Error> {
+        Ok(self
+            .client
+            .get(&self.url)
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+}
+
+/// Fetched results of unfinished games that are neither pending nor rejected with the same
+/// score. Games are matched by `Game::external_id`, or by their short name without one.
+fn proposals(d: &DataInter, source: &str, results: &[ExternalResult]) -> Vec<PendingResult> {
+    results
+        .iter()
+        .filter(|r| r.finished)
+        .filter_map(|result| {
+            let game = d.games.iter().find(|g| {
+                g.result.is_none() && g.external_id.as_ref().unwrap_or(&g.short) == &result.id
+            })?;
+            Some(PendingResult {
+                short: game.short.clone(),
+                team1: result.team1,
+                team2: result.team2,
+                source: source.to_string(),
+                rejected: false,
+            })
+        })
+        .filter(|pending| {
+            !d.pending_results.iter().any(|p| {
+                p.short == pending.short && p.team1 == pending.team1 && p.team2 == pending.team2
+            })
+        })
+        .unique_by(|pending| pending.short.clone())
+        .collect()
+}
+
+/// Queues new proposals for confirmation, replacing older ones for the same game.
+fn queue(d: &mut DataInter, source: &str, results: &[ExternalResult]) -> usize {
+    let proposals = proposals(d, source, results);
+    for pending in &proposals {
+        d.pending_results.retain(|p| p.short != pending.short);
+    }
+    let queued = proposals.len();
+    d.pending_results.extend(proposals);
+    queued
+}
+
+/// Polls `RESULTS_FEED_URL` every `RESULTS_POLL_SECONDS` if it is set.
+pub async fn watch_feed(data: Data) {
+    let Ok(url) = env::var("RESULTS_FEED_URL") else {
+        return;
+    };
+    let seconds = env::var("RESULTS_POLL_SECONDS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_POLL_SECONDS);
+
+    poll(data, JsonFeed::new(url), Duration::from_secs(seconds)).await;
+}
+
+pub async fn poll(data: Data, provider: impl ResultsProvider, every: Duration) {
+    let mut interval = tokio::time::interval(every);
+    loop {
+        interval.tick().await;
+
+        match provider.fetch().await {
+            Ok(results) => {
+                // Only lock for writing (and saving) if there is something new.
+                if proposals(&*data.read().await, &provider.name(), &results).is_empty() {
+                    continue;
+                }
+                let queued = queue(&mut *data.lock().await, &provider.name(), &results);
+                println!("{queued} new result(s) from {} to confirm", provider.name());
+            }
+            Err(why) => eprintln!("Fetching results from {} failed: {why}", provider.name()),
+        }
+    }
+}
+
+async fn pending_autocomplete<'a>(
+    ctx: PoiseContext<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = String> + 'a {
+    let d = ctx.data.read().await;
+    let pending = d
+        .pending_results
+        .iter()
+        .filter(|p| !p.rejected)
+        .map(|p| format!("{}:{} '{}'", p.team1, p.team2, p.short))
+        .collect::<Vec<_>>();
+    serenity::futures::stream::iter(pending)
+        .filter(move |n| serenity::futures::future::ready(n.contains(partial)))
+}
+
+fn parse_short(game: &str) -> Result<String, Error> {
+    Ok(game
+        .split('\'')
+        .nth(1)
+        .ok_or("Game could not be parsed!")?
+        .to_string())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn set_external_id(
+    ctx: PoiseContext<'_>,
+    #[description = "Das Kürzel des Spiels"] short: String,
+    #[description = "Die Id des Spiels beim Ergebnisdienst (leer lassen zum Entfernen)"]
+    external_id: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let mut d = ctx.data().lock().await;
+    let game = d
+        .games
+        .iter_mut()
+        .find(|g| g.short == short)
+        .ok_or("Kürzel gehört zu keinem Spiel")?;
+    game.external_id = external_id;
+
+    ctx.reply("Succesful").await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn pending_results(ctx: PoiseContext<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let d = ctx.data().read().await;
+    let lines = d
+        .pending_results
+        .iter()
+        .filter(|p| !p.rejected)
+        .map(|p| {
+            let name = d
+                .games
+                .iter()
+                .find(|g| g.short == p.short)
+                .map(|g| g.name.clone())
+                .unwrap_or(p.short.clone());
+            format!(
+                "{name} ({}): {}:{} von {}",
+                p.short, p.team1, p.team2, p.source
+            )
+        })
+        .join("\n");
+
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content(if lines.is_empty() {
+                "Keine Ergebnisse zu bestätigen.".to_string()
+            } else {
+                format!("# Zu bestätigende Ergebnisse\n{lines}")
+            }),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn confirm_result(
+    ctx: PoiseContext<'_>,
+    #[description = "Das abgerufene Ergebnis"]
+    #[autocomplete = "pending_autocomplete"]
+    game: String,
+    #[description = "Ergebnis im Kanal verkünden (Standard: wenn es Seltenheit, Coins oder Survivor betrifft)"]
+    announce: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let short = parse_short(&game)?;
+    let mut d = ctx.data().lock().await;
+    let pending = d
+        .pending_results
+        .iter()
+        .find(|p| p.short == short && !p.rejected)
+        .cloned()
+        .ok_or("Für dieses Spiel liegt kein Ergebnis vor!")?;
+
+    enter_result(
+        &mut d,
+        &short,
+        (pending.team1, pending.team2, String::new()),
+    )
+    .ok_or("Kürzel gehört zu keinem Spiel")?;
+
+    if announce.unwrap_or_else(|| has_news(&d, &short)) {
+        ctx.channel_id()
+            .say(&ctx, result_announcement(&d, &short))
+            .await?;
+    }
+
+    ctx.reply(format!(
+        "Ergebnis {}:{} übernommen.",
+        pending.team1, pending.team2
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn reject_result(
+    ctx: PoiseContext<'_>,
+    #[description = "Das abgerufene Ergebnis"]
+    #[autocomplete = "pending_autocomplete"]
+    game: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let short = parse_short(&game)?;
+    let mut d = ctx.data().lock().await;
+    for pending in d.pending_results.iter_mut().filter(|p| p.short == short) {
+        pending.rejected = true;
+    }
+
+    ctx.reply(
+        "Ergebnis verworfen. Es wird erst wieder vorgeschlagen, wenn sich der Spielstand ändert.",
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Router;
+
+    use super::*;
+    use crate::data::Game;
+    use crate::testing::serve;
+
+    fn game(short: &str, external_id: Option<&str>) -> Game {
+        Game {
+            external_id: external_id.map(String::from),
+            ..crate::testing::game(short)
+        }
+    }
+
+    fn result(id: &str, team1: u16, team2: u16) -> ExternalResult {
+        ExternalResult {
+            id: id.to_string(),
+            team1,
+            team2,
+            finished: true,
+        }
+    }
+
+    fn data() -> DataInter {
+        DataInter {
+            games: vec![game("A1", Some("4711")), game("A2", None)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn queue_matches_external_id_or_short() {
+        let mut d = data();
+        let results = [
+            result("4711", 5, 1),
+            result("A2", 0, 0),
+            // A1 has an external id, so its short name must not match.
+            result("A1", 3, 3),
+            result("unknown", 1, 1),
+        ];
+
+        assert_eq!(queue(&mut d, "feed", &results), 2);
+        let pending = d
+            .pending_results
+            .iter()
+            .map(|p| (p.short.as_str(), p.team1, p.team2))
+            .collect::<Vec<_>>();
+        assert_eq!(pending, [("A1", 5, 1), ("A2", 0, 0)]);
+        assert!(d.pending_results.iter().all(|p| p.source == "feed"));
+    }
+
+    #[test]
+    fn queue_skips_unfinished_and_entered_games() {
+        let mut d = data();
+        d.games[1].result = Some((2, 2, String::new()));
+        let mut running = result("4711", 1, 0);
+        running.finished = false;
+
+        assert_eq!(queue(&mut d, "feed", &[running, result("A2", 2, 2)]), 0);
+        assert!(d.pending_results.is_empty());
+    }
+
+    #[test]
+    fn queue_skips_known_scores_and_proposes_changed_ones() {
+        let mut d = data();
+        assert_eq!(queue(&mut d, "feed", &[result("A2", 1, 0)]), 1);
+        assert_eq!(queue(&mut d, "feed", &[result("A2", 1, 0)]), 0);
+
+        d.pending_results[0].rejected = true;
+        assert_eq!(queue(&mut d, "feed", &[result("A2", 1, 0)]), 0);
+        assert!(d.pending_results[0].rejected);
+
+        assert_eq!(queue(&mut d, "feed", &[result("A2", 2, 0)]), 1);
+        assert_eq!(d.pending_results.len(), 1);
+        assert_eq!(
+            (d.pending_results[0].team1, d.pending_results[0].team2),
+            (2, 0)
+        );
+        assert!(!d.pending_results[0].rejected);
+    }
+
+    #[tokio::test]
+    async fn json_feed_fetches_results() {
+        let url = serve(Router::new().route(
+            "/results",
+            get(|| async {
+                r#"[{"id": "4711", "team1": 5, "team2": 1},
+                    {"id": "A2", "team1": 0, "team2": 0, "finished": false}]"#
+            }),
+        ))
+        .await;
+
+        let results = JsonFeed::new(format!("{url}/results"))
+            .fetch()
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            (results[0].id.as_str(), results[0].team1, results[0].team2),
+            ("4711", 5, 1)
+        );
+        assert!(results[0].finished);
+        assert!(!results[1].finished);
+    }
+
+    #[tokio::test]
+    async fn json_feed_reports_errors() {
+        let url = serve(Router::new().route(
+            "/results",
+            get(|| async { StatusCode::SERVICE_UNAVAILABLE }),
+        ))
+        .await;
+
+        assert!(JsonFeed::new(format!("{url}/results"))
+            .fetch()
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use serenity::all::UserId;
+
+use crate::data::{default_tiebreakers, Bet, DataInter, Game, Outcome, TieBreaker};
+use crate::{JOKER_FACTOR, POINTS_CORRECT, POINTS_TEAM, POINTS_TENDENZ};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Hit {
+    Exact,
+    Tendency,
+    Winner,
+    Miss,
+}
+
+pub fn hit(bet: &Bet, result: &(u16, u16, String)) -> Hit {
+    if bet.team1 == result.0 && bet.team2 == result.1 {
+        Hit::Exact
+    } else if (bet.team1 as i16 - bet.team2 as i16) == (result.0 as i16 - result.1 as i16) {
+        Hit::Tendency
+    } else if (bet.team1 > bet.team2 && result.0 > result.1)
+        || (bet.team1 < bet.team2 && result.0 < result.1)
+    {
+        Hit::Winner
+    } else {
+        Hit::Miss
+    }
+}
+
+pub fn tip_points(bet: &Bet, result: &(u16, u16, String)) -> u32 {
+    match hit(bet, result) {
+        Hit::Exact => POINTS_CORRECT,
+        Hit::Tendency => POINTS_TENDENZ,
+        Hit::Winner => POINTS_TEAM,
+        Hit::Miss => 0,
+    }
+}
+
+/// Points a bet earned including the game modifier, a placed joker and the
+/// confidence weight. `None` while the game has no result.
+pub fn bet_points(d: &DataInter, game: &Game, bet: &Bet) -> Option<u32> {
+    let result = game.result.as_ref()?;
+    let mut points = tip_points(bet, result) * game.modifier;
+    if bet.joker {
+        points *= JOKER_FACTOR;
+    }
+    if d.settings.confidence {
+        points *= bet.weight.unwrap_or(1);
+    }
+    if d.settings.rarity {
+        if let Some((same, total)) = rarity(d, game) {
+            points = (points * (2 * total - same) + total / 2) / total;
+        }
+    }
+    Some(points)
+}
+
+/// How many of all tips on a finished game predicted its outcome, as
+/// `(same, total)`. Rarity scoring multiplies points by `(2 * total - same) / total`,
+/// so a tip shared by everybody keeps its points and a lone call nearly doubles them.
+pub fn rarity(d: &DataInter, game: &Game) -> Option<(u32, u32)> {
+    let result = game.result.as_ref()?;
+    let outcome = Outcome::of(result.0, result.1);
+    let bets = d.bets.get(&game.short)?;
+    if bets.is_empty() {
+        return None;
+    }
+
+    let same = bets
+        .iter()
+        .filter(|b| Outcome::of(b.team1, b.team2) == outcome)
+        .count() as u32;
+    Some((same, bets.len() as u32))
+}
+
+/// What a user collected so far: the points plus the counters the
+/// tiebreakers look at.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Tally {
+    pub points: u32,
+    pub exact: u32,
+    pub tendency: u32,
+    pub global: u32,
+}
+
+impl Tally {
+    pub fn add_bet(&mut self, d: &DataInter, game: &Game, bet: &Bet) {
+        let Some(result) = game.result.as_ref() else {
+            return;
+        };
+        self.points += bet_points(d, game, bet).unwrap_or(0);
+        match hit(bet, result) {
+            Hit::Exact => self.exact += 1,
+            Hit::Tendency => self.tendency += 1,
+            _ => {}
+        }
+    }
+
+    pub fn add_global(&mut self, points: u32) {
+        self.points += points;
+        self.global += points;
+    }
+}
+
+pub fn user_tallies(d: &DataInter) -> HashMap<UserId, Tally> {
+    let mut tallies: HashMap<UserId, Tally> = HashMap::new();
+    for (game_short, bets) in d.bets.iter() {
+        // Bets on deleted games only show up in `tippy-admin validate`.
+        let Some(g) = d.games.iter().find(|g| &g.short == game_short) else {
+            continue;
+        };
+        for bet in bets {
+            tallies.entry(bet.user).or_default().add_bet(d, g, bet);
+        }
+    }
+
+    for b in d.global_bets.values() {
+        for (user, tip) in b.bets.iter() {
+            let points = b.points_for(tip);
+            if points > 0 {
+                tallies.entry(*user).or_default().add_global(points);
+            }
+        }
+    }
+
+    tallies
+}
+
+pub fn user_points(d: &DataInter) -> HashMap<UserId, u32> {
+    user_tallies(d)
+        .into_iter()
+        .map(|(u, t)| (u, t.points))
+        .collect()
+}
+
+pub struct Standing {
+    pub user: UserId,
+    pub tally: Tally,
+    pub rank: usize,
+}
+
+/// Goals scored in all finished games so far, the answer to the tie-break question.
+pub fn total_goals(d: &DataInter) -> u32 {
+    d.games
+        .iter()
+        .filter_map(|g| g.result.as_ref())
+        .map(|r| (r.0 + r.1) as u32)
+        .sum()
+}
+
+/// The tie-break question only has an answer once every game has a result.
+pub fn all_games_finished(d: &DataInter) -> bool {
+    !d.games.is_empty() && d.games.iter().all(|g| g.result.is_some())
+}
+
+fn ranking_key(d: &DataInter, user: UserId, tally: &Tally) -> (u32, Vec<i64>) {
+    let goals = all_games_finished(d).then(|| total_goals(d));
+    let tiebreakers = d
+        .settings
+        .tiebreakers
+        .clone()
+        .unwrap_or_else(default_tiebreakers);
+    let keys = tiebreakers
+        .iter()
+        .map(|t| match t {
+            TieBreaker::Exact => tally.exact as i64,
+            TieBreaker::Tendency => tally.tendency as i64,
+            TieBreaker::Global => tally.global as i64,
+            // Closer is better, no answer is worst. Neutral while games are open.
+            TieBreaker::Question => match goals {
+                Some(goals) => d
+                    .goal_guesses
+                    .get(&user)
+                    .map(|g| -(*g as i64 - goals as i64).abs())
+                    .unwrap_or(i64::MIN),
+                None => 0,
+            },
+        })
+        .collect();
+    (tally.points, keys)
+}
+
+/// Sorts by points and the configured tiebreakers. Users that are equal on
+/// all of them share a rank and are listed by id.
+pub fn standings(d: &DataInter, tallies: &HashMap<UserId, Tally>) -> Vec<Standing> {
+    let sorted = tallies
+        .iter()
+        .map(|(u, t)| (ranking_key(d, *u, t), *u, *t))
+        .sorted_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)))
+        .collect::<Vec<_>>();
+
+    let mut standings: Vec<Standing> = Vec::new();
+    for (i, (key, user, tally)) in sorted.iter().enumerate() {
+        let rank = match standings.last() {
+            Some(last) if i > 0 && sorted[i - 1].0 == *key => last.rank,
+            _ => i + 1,
+        };
+        standings.push(Standing {
+            user: *user,
+            tally: *tally,
+            rank,
+        });
+    }
+
+    standings
+}
+
+pub fn ranks(d: &DataInter, tallies: &HashMap<UserId, Tally>) -> HashMap<UserId, usize> {
+    standings(d, tallies)
+        .into_iter()
+        .map(|s| (s.user, s.rank))
+        .collect()
+}
+
+pub struct Snapshot {
+    pub matchday: String,
+    pub label: String,
+    pub tallies: HashMap<UserId, Tally>,
+}
+
+/// Tallies of every participant after each finished game and each resolved
+/// global bet, in chronological order. Global bets count towards the matchday
+/// of the game before them.
+pub fn timeline(d: &DataInter) -> Vec<Snapshot> {
+    let mut tallies: HashMap<UserId, Tally> = user_tallies(d)
+        .keys()
+        .map(|u| (*u, Tally::default()))
+        .collect();
+    let mut snapshots: Vec<Snapshot> = Vec::new();
+
+    let games = d
+        .games
+        .iter()
+        .filter(|g| g.result.is_some())
+        .map(|g| (g.start_time, Some(g), None));
+    let global_bets = d
+        .global_bets
+        .values()
+        .filter(|b| b.result.is_some())
+        .map(|b| (b.resolved_at.unwrap_or(b.start_time), None, Some(b)));
+
+    for (_, game, global_bet) in games
+        .chain(global_bets)
+        .sorted_by_key(|(time, game, _)| (*time, game.is_none()))
+    {
+        let (matchday, label) = if let Some(game) = game {
+            for bet in d.bets.get(&game.short).into_iter().flatten() {
+                tallies.entry(bet.user).or_default().add_bet(d, game, bet);
+            }
+            (game.matchday_name(), game.name.clone())
+        } else if let Some(global_bet) = global_bet {
+            for (user, tip) in global_bet.bets.iter() {
+                tallies
+                    .entry(*user)
+                    .or_default()
+                    .add_global(global_bet.points_for(tip));
+            }
+            (
+                snapshots
+                    .last()
+                    .map(|s| s.matchday.clone())
+                    .unwrap_or(global_bet.name.clone()),
+                global_bet.name.clone(),
+            )
+        } else {
+            continue;
+        };
+
+        snapshots.push(Snapshot {
+            matchday,
+            label,
+            tallies: tallies.clone(),
+        });
+    }
+
+    snapshots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::TieBreaker;
+    use crate::testing::game;
+
+    fn ranked(d: &DataInter) -> Vec<(u64, usize)> {
+        let tallies = [1, 2]
+            .into_iter()
+            .map(|u| (UserId::new(u), Tally::default()))
+            .collect();
+        standings(d, &tallies)
+            .into_iter()
+            .map(|s| (s.user.get(), s.rank))
+            .collect()
+    }
+
+    #[test]
+    fn goal_question_decides_only_after_the_last_game() {
+        let mut d = DataInter {
+            games: vec![
+                Game {
+                    result: Some((5, 1, String::new())),
+                    ..game("A1")
+                },
+                game("A2"),
+            ],
+            ..Default::default()
+        };
+        d.settings.tiebreakers = Some(vec![TieBreaker::Question]);
+        d.goal_guesses.insert(UserId::new(1), 6);
+        d.goal_guesses.insert(UserId::new(2), 9);
+
+        assert_eq!(ranked(&d), [(1, 1), (2, 1)]);
+
+        d.games[1].result = Some((2, 1, String::new()));
+        assert_eq!(ranked(&d), [(2, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn bets_on_unknown_games_are_ignored() {
+        let bet = |user, team1, team2| crate::data::Bet {
+            user: UserId::new(user),
+            team1,
+            team2,
+            joker: false,
+            weight: None,
+        };
+        let mut d = DataInter {
+            games: vec![Game {
+                result: Some((2, 0, String::new())),
+                ..game("A1")
+            }],
+            ..Default::default()
+        };
+        d.bets.insert("A1".to_string(), vec![bet(1, 2, 0)]);
+        d.bets
+            .insert("XX".to_string(), vec![bet(1, 1, 0), bet(2, 0, 0)]);
+
+        let tallies = user_tallies(&d);
+        assert_eq!(tallies.len(), 1);
+        assert_eq!(tallies[&UserId::new(1)].exact, 1);
+    }
+}
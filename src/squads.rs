@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use ascii_table::AsciiTable;
+use itertools::Itertools;
+use poise::futures_util::StreamExt;
+use poise::{Command, CreateReply};
+use serenity::all::UserId;
+use serenity::futures::Stream;
+
+use crate::data::{Data, DataInter, Squad, SquadScoring};
+use crate::scoring::user_points;
+use crate::{Error, PoiseContext};
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![
+        squad_gruenden(),
+        squad_beitreten(),
+        squad_verlassen(),
+        squads(),
+        set_squad_scoring(),
+    ]
+}
+
+#[derive(Debug, poise::ChoiceParameter)]
+enum SquadMode {
+    #[name = "Summe"]
+    Sum,
+    #[name = "Durchschnitt"]
+    Average,
+    #[name = "Beste N"]
+    Top,
+}
+
+pub fn squad_points(d: &DataInter, squad: &Squad, points: &HashMap<UserId, u32>) -> f64 {
+    let member_points = squad
+        .members
+        .iter()
+        .map(|m| points.get(m).copied().unwrap_or(0))
+        .sorted()
+        .rev()
+        .collect::<Vec<_>>();
+
+    match d.settings.squad_scoring {
+        SquadScoring::Sum => member_points.iter().sum::<u32>() as f64,
+        SquadScoring::Average if member_points.is_empty() => 0.0,
+        SquadScoring::Average => {
+            member_points.iter().sum::<u32>() as f64 / member_points.len() as f64
+        }
+        SquadScoring::Top(n) => member_points.iter().take(n as usize).sum::<u32>() as f64,
+    }
+}
+
+pub fn squad_table_string(d: &DataInter) -> String {
+    let mut table = AsciiTable::default();
+    table.column(0).set_header("#");
+    table.column(1).set_header("Squad");
+    table.column(2).set_header("Mitglieder");
+    table.column(3).set_header(match d.settings.squad_scoring {
+        SquadScoring::Sum => "Punkte".to_string(),
+        SquadScoring::Average => "Ø Punkte".to_string(),
+        SquadScoring::Top(n) => format!("Punkte (Top {n})"),
+    });
+
+    let points = user_points(d);
+    let squads = d
+        .squads
+        .values()
+        .map(|s| (s, squad_points(d, s, &points)))
+        .sorted_by(|a, b| b.1.total_cmp(&a.1).then(a.0.name.cmp(&b.0.name)))
+        .collect::<Vec<_>>();
+    let data = squads
+        .iter()
+        .map(|(s, p)| {
+            let rank = 1 + squads.iter().filter(|(_, o)| o > p).count();
+            vec![
+                format!("{rank}."),
+                s.name.clone(),
+                s.members.len().to_string(),
+                if d.settings.squad_scoring == SquadScoring::Average {
+                    format!("{p:.1}")
+                } else {
+                    p.to_string()
+                },
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    table.format(data)
+}
+
+async fn squad_autocomplete<'a>(
+    ctx: PoiseContext<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = String> + 'a {
+    let d = ctx.data.read().await;
+    let names = d.squads.keys().cloned().sorted().collect::<Vec<_>>();
+    serenity::futures::stream::iter(names)
+        .filter(move |n| serenity::futures::future::ready(n.starts_with(partial)))
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn squad_gruenden(
+    ctx: PoiseContext<'_>,
+    #[description = "Name des Squads"] name: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user = ctx.author().id;
+    let mut d = ctx.data().lock().await;
+    if d.squads.contains_key(&name) {
+        ctx.reply("Ein Squad mit diesem Namen existiert bereits!")
+            .await?;
+        return Ok(());
+    }
+
+    leave_squad(&mut d, user);
+    d.squads.insert(
+        name.clone(),
+        Squad {
+            name: name.clone(),
+            members: vec![user],
+        },
+    );
+
+    ctx.reply(format!("Squad {name} gegründet!")).await?;
+
+    Ok(())
+}
+
+fn leave_squad(d: &mut DataInter, user: UserId) {
+    for squad in d.squads.values_mut() {
+        squad.members.retain(|m| *m != user);
+    }
+    d.squads.retain(|_, s| !s.members.is_empty());
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn squad_beitreten(
+    ctx: PoiseContext<'_>,
+    #[description = "Der Squad, dem du beitreten willst"]
+    #[autocomplete = "squad_autocomplete"]
+    squad: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user = ctx.author().id;
+    let mut d = ctx.data().lock().await;
+    if !d.squads.contains_key(&squad) {
+        ctx.reply("Squad existiert nicht!").await?;
+        return Ok(());
+    }
+
+    leave_squad(&mut d, user);
+    d.squads
+        .get_mut(&squad)
+        .ok_or("Squad existiert nicht!")?
+        .members
+        .push(user);
+
+    ctx.reply(format!("Du bist jetzt Mitglied von {squad}!"))
+        .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn squad_verlassen(ctx: PoiseContext<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let mut d = ctx.data().lock().await;
+    leave_squad(&mut d, ctx.author().id);
+
+    ctx.reply("Du hast deinen Squad verlassen.").await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn squads(ctx: PoiseContext<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let d = ctx.data().read().await;
+    let table_string = squad_table_string(&d);
+
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content(format!("# Squads\n```\n{table_string}\n```")),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn set_squad_scoring(
+    ctx: PoiseContext<'_>,
+    #[description = "Wie die Punkte der Mitglieder zusammengezählt werden"] modus: SquadMode,
+    #[description = "Anzahl der besten Mitglieder für \"Beste N\""]
+    #[min = 1]
+    n: Option<u32>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let scoring = match modus {
+        SquadMode::Sum => SquadScoring::Sum,
+        SquadMode::Average => SquadScoring::Average,
+        SquadMode::Top => SquadScoring::Top(n.ok_or("Für \"Beste N\" muss n angegeben werden!")?),
+    };
+
+    let mut d = ctx.data().lock().await;
+    d.settings.squad_scoring = scoring;
+
+    ctx.reply("Squad-Wertung gespeichert.").await?;
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ascii_table::AsciiTable;
+use itertools::Itertools;
+use poise::{Command, CreateReply};
+use serenity::all::User;
+
+use crate::cmds::get_now;
+use crate::data::{Bet, Data, DataInter, Game};
+use crate::scoring::{bet_points, hit, ranks, timeline, Hit};
+use crate::{Error, PoiseContext};
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![stats()]
+}
+
+fn percent(part: usize, total: usize) -> String {
+    if total == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.0}%", part as f64 * 100.0 / total as f64)
+    }
+}
+
+fn user_bets<'a>(d: &'a DataInter, user: &User) -> Vec<(&'a Game, &'a Bet)> {
+    d.games
+        .iter()
+        .sorted_by_key(|g| g.start_time)
+        .filter_map(|g| {
+            let bet = d.bets.get(&g.short)?.iter().find(|b| b.user == user.id)?;
+            Some((g, bet))
+        })
+        .collect()
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn stats(
+    ctx: PoiseContext<'_>,
+    #[description = "Spieler (Standard: du selbst)"] user: Option<User>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user = user.as_ref().unwrap_or_else(|| ctx.author());
+    let d = ctx.data().read().await;
+
+    // Only started games, so nobody can peek at open tips of others.
+    let bets = user_bets(&d, user)
+        .into_iter()
+        .filter(|(g, _)| g.start_time <= get_now())
+        .collect::<Vec<_>>();
+    let finished = bets
+        .iter()
+        .filter_map(|(g, b)| {
+            let result = g.result.as_ref()?;
+            Some((*g, *b, hit(b, result), bet_points(&d, g, b)?))
+        })
+        .collect::<Vec<_>>();
+
+    if finished.is_empty() {
+        ctx.reply(format!("{} hat noch keine gewerteten Tipps.", user.name))
+            .await?;
+        return Ok(());
+    }
+
+    // HITS
+
+    let mut hits_table = AsciiTable::default();
+    hits_table.column(0).set_header("Treffer");
+    hits_table.column(1).set_header("Anzahl");
+    hits_table.column(2).set_header("Anteil");
+
+    let hits_data = [
+        (Hit::Exact, "Exakt"),
+        (Hit::Tendency, "Tendenz"),
+        (Hit::Winner, "Sieger"),
+        (Hit::Miss, "Daneben"),
+    ]
+    .iter()
+    .map(|(h, name)| {
+        let count = finished.iter().filter(|f| f.2 == *h).count();
+        vec![
+            name.to_string(),
+            count.to_string(),
+            percent(count, finished.len()),
+        ]
+    })
+    .collect::<Vec<_>>();
+
+    let total = finished.iter().map(|f| f.3).sum::<u32>();
+
+    // MATCHDAYS AND MODIFIERS
+
+    let mut matchdays: Vec<(String, usize, u32)> = Vec::new();
+    for (g, _, _, points) in &finished {
+        let matchday = g.matchday_name();
+        match matchdays.last_mut() {
+            Some(last) if last.0 == matchday => {
+                last.1 += 1;
+                last.2 += points;
+            }
+            _ => matchdays.push((matchday, 1, *points)),
+        }
+    }
+
+    let mut modifiers: BTreeMap<u32, (usize, u32)> = BTreeMap::new();
+    for (g, _, _, points) in &finished {
+        let entry = modifiers.entry(g.modifier).or_default();
+        entry.0 += 1;
+        entry.1 += points;
+    }
+
+    let best = matchdays.iter().max_by_key(|m| m.2).unwrap();
+    let worst = matchdays.iter().min_by_key(|m| m.2).unwrap();
+
+    let mut matchday_table = AsciiTable::default();
+    matchday_table.column(0).set_header("Spieltag");
+    matchday_table.column(1).set_header("Spiele");
+    matchday_table.column(2).set_header("Punkte");
+    let matchday_data = matchdays
+        .iter()
+        .map(|(m, games, points)| vec![m.clone(), games.to_string(), points.to_string()])
+        .collect::<Vec<_>>();
+
+    let mut modifier_table = AsciiTable::default();
+    modifier_table.column(0).set_header("Modifier");
+    modifier_table.column(1).set_header("Spiele");
+    modifier_table.column(2).set_header("Punkte");
+    let modifier_data = modifiers
+        .iter()
+        .map(|(m, (games, points))| vec![format!("x{m}"), games.to_string(), points.to_string()])
+        .collect::<Vec<_>>();
+
+    // RANK OVER TIME
+
+    let ranks = timeline(&d)
+        .into_iter()
+        .rev()
+        .unique_by(|s| s.matchday.clone())
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .map(|s| {
+            ranks(&d, &s.tallies)
+                .get(&user.id)
+                .map(|r| format!("{r}."))
+                .unwrap_or("-".to_string())
+        })
+        .join(" → ");
+
+    // BIASES
+
+    let results = d
+        .games
+        .iter()
+        .filter_map(|g| g.result.as_ref())
+        .collect::<Vec<_>>();
+    let tipped_draws = bets.iter().filter(|(_, b)| b.team1 == b.team2).count();
+    let actual_draws = results.iter().filter(|r| r.0 == r.1).count();
+    let tipped_home = bets.iter().filter(|(_, b)| b.team1 > b.team2).count();
+    let actual_home = results.iter().filter(|r| r.0 > r.1).count();
+    let tipped_goals = bets
+        .iter()
+        .map(|(_, b)| (b.team1 + b.team2) as f64)
+        .sum::<f64>()
+        / bets.len() as f64;
+    let actual_goals =
+        results.iter().map(|r| (r.0 + r.1) as f64).sum::<f64>() / results.len() as f64;
+
+    let mut favourites: HashMap<&str, usize> = HashMap::new();
+    for (g, b) in &bets {
+        if b.team1 > b.team2 {
+            *favourites.entry(&g.team1_iso).or_default() += 1;
+        } else if b.team2 > b.team1 {
+            *favourites.entry(&g.team2_iso).or_default() += 1;
+        }
+    }
+    let favourite = favourites
+        .iter()
+        .max_by_key(|(iso, count)| (**count, std::cmp::Reverse(**iso)))
+        .map(|(iso, count)| {
+            let name = d
+                .teams
+                .iter()
+                .find(|t| t.iso == *iso)
+                .map(|t| format!("{} {}", t.name, t.flag))
+                .unwrap_or(iso.to_string());
+            format!("{name} ({count}x als Sieger getippt)")
+        })
+        .unwrap_or("-".to_string());
+
+    let summary = [
+        format!("# Statistik für {}", user.name),
+        format!("```\n{}\n```", hits_table.format(hits_data)),
+        format!(
+            "**Ø Punkte pro Spiel:** {:.2} ({total} Punkte aus {} Spielen)",
+            total as f64 / finished.len() as f64,
+            finished.len()
+        ),
+        format!("**Bester Spieltag:** {} ({} Punkte)", best.0, best.2),
+        format!(
+            "**Schlechtester Spieltag:** {} ({} Punkte)",
+            worst.0, worst.2
+        ),
+        format!("**Platzierung nach Spieltagen:** {ranks}"),
+        "## Tendenzen".to_string(),
+        format!(
+            "Unentschieden getippt: {} (tatsächlich {})",
+            percent(tipped_draws, bets.len()),
+            percent(actual_draws, results.len())
+        ),
+        format!(
+            "Heimsiege getippt: {} (tatsächlich {})",
+            percent(tipped_home, bets.len()),
+            percent(actual_home, results.len())
+        ),
+        format!("Ø Tore getippt: {tipped_goals:.1} (tatsächlich {actual_goals:.1})"),
+        format!("Lieblingsteam: {favourite}"),
+    ]
+    .join("\n");
+
+    ctx.send(CreateReply::default().ephemeral(true).content(summary))
+        .await?;
+
+    ctx.send(CreateReply::default().ephemeral(true).content(format!(
+        "## Punkte pro Spieltag\n```\n{}\n```\n## Punkte pro Modifier\n```\n{}\n```",
+        matchday_table.format(matchday_data),
+        modifier_table.format(modifier_data)
+    )))
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,263 @@
+use ascii_table::AsciiTable;
+use itertools::Itertools;
+use poise::{Command, CreateReply};
+use serenity::all::UserId;
+
+use crate::cmds::{country_autocomplete, get_now};
+use crate::data::{Data, DataInter, Game, SurvivorPick};
+use crate::{Error, PoiseContext};
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![survivor(), survivor_tabelle()]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Alive,
+    Out { matchday: String, reason: String },
+}
+
+/// Matchdays in kickoff order together with their games.
+fn matchdays(d: &DataInter) -> Vec<(String, Vec<&Game>)> {
+    d.games
+        .iter()
+        .sorted_by_key(|g| g.start_time)
+        .map(|g| (g.matchday_name(), g))
+        .into_group_map()
+        .into_iter()
+        .sorted_by_key(|(_, games)| games.iter().map(|g| g.start_time).min())
+        .collect()
+}
+
+/// The matchday picks are currently made for: the first one with a game
+/// that has not kicked off yet.
+fn open_matchday(d: &DataInter) -> Option<String> {
+    matchdays(d)
+        .into_iter()
+        .find(|(_, games)| games.iter().any(|g| g.start_time > get_now()))
+        .map(|(m, _)| m)
+}
+
+pub fn status(d: &DataInter, user: UserId) -> Status {
+    let rounds = matchdays(d);
+    let first = rounds
+        .iter()
+        .position(|(m, _)| d.survivor.iter().any(|p| &p.matchday == m));
+
+    for (matchday, games) in rounds.iter().skip(first.unwrap_or(rounds.len())) {
+        let Some(pick) = d
+            .survivor
+            .iter()
+            .find(|p| p.user == user && &p.matchday == matchday)
+        else {
+            if games.iter().all(|g| g.start_time <= get_now()) {
+                return Status::Out {
+                    matchday: matchday.clone(),
+                    reason: "Kein Tipp abgegeben".to_string(),
+                };
+            }
+            continue;
+        };
+
+        let Some(game) = games.iter().find(|g| g.short == pick.game) else {
+            continue;
+        };
+        let Some(result) = &game.result else {
+            continue;
+        };
+
+        let (own, other) = if game.team1_iso == pick.team_iso {
+            (result.0, result.1)
+        } else {
+            (result.1, result.0)
+        };
+        if own <= other {
+            return Status::Out {
+                matchday: matchday.clone(),
+                reason: format!(
+                    "{} {} {}:{}",
+                    if own == other {
+                        "Unentschieden"
+                    } else {
+                        "Niederlage"
+                    },
+                    pick.team_iso,
+                    own,
+                    other
+                ),
+            };
+        }
+    }
+
+    Status::Alive
+}
+
+pub fn participants(d: &DataInter) -> Vec<UserId> {
+    d.survivor.iter().map(|p| p.user).unique().collect()
+}
+
+/// Users knocked out by the result of `game_short`.
+pub fn eliminated_by(d: &DataInter, game_short: &str) -> Vec<(UserId, String)> {
+    let Some(game) = d.games.iter().find(|g| g.short == game_short) else {
+        return vec![];
+    };
+    let matchday = game.matchday_name();
+
+    d.survivor
+        .iter()
+        .filter(|p| p.game == game_short)
+        .filter_map(|p| match status(d, p.user) {
+            Status::Out {
+                matchday: m,
+                reason,
+            } if m == matchday => Some((p.user, reason)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn survivor(
+    ctx: PoiseContext<'_>,
+    #[description = "Das Team, das am aktuellen Spieltag gewinnt"]
+    #[autocomplete = "country_autocomplete"]
+    country: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let iso = country
+        .split('\'')
+        .nth(1)
+        .ok_or("Country could not be parsed!")?
+        .to_string();
+    let user = ctx.author().id;
+
+    let mut d = ctx.data().lock().await;
+    let team = d
+        .teams
+        .iter()
+        .find(|t| t.iso == iso)
+        .cloned()
+        .ok_or("Country not valid")?;
+
+    if let Status::Out { matchday, reason } = status(&d, user) {
+        ctx.reply(format!(
+            "Du bist bereits ausgeschieden ({matchday}: {reason})!"
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let matchday = open_matchday(&d).ok_or("Es gibt keinen offenen Spieltag mehr!")?;
+
+    if let Some(p) = d
+        .survivor
+        .iter()
+        .find(|p| p.user == user && p.team_iso == iso && p.matchday != matchday)
+    {
+        ctx.reply(format!(
+            "{} hast du bereits an {} getippt!",
+            team.name, p.matchday
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let Some(game) = d
+        .games
+        .iter()
+        .find(|g| g.matchday_name() == matchday && (g.team1_iso == iso || g.team2_iso == iso))
+        .cloned()
+    else {
+        ctx.reply(format!("{} spielt nicht an {matchday}!", team.name))
+            .await?;
+        return Ok(());
+    };
+
+    if game.start_time <= get_now() {
+        ctx.reply("Das Spiel hat bereits begonnen!").await?;
+        return Ok(());
+    }
+
+    if let Some(i) = d
+        .survivor
+        .iter()
+        .position(|p| p.user == user && p.matchday == matchday)
+    {
+        let old = &d.survivor[i];
+        let locked = d
+            .games
+            .iter()
+            .any(|g| g.short == old.game && g.start_time <= get_now());
+        if locked {
+            ctx.reply("Dein Tipp für diesen Spieltag kann nicht mehr verändert werden!")
+                .await?;
+            return Ok(());
+        }
+        d.survivor.remove(i);
+    }
+
+    d.survivor.push(SurvivorPick {
+        user,
+        matchday: matchday.clone(),
+        game: game.short.clone(),
+        team_iso: iso,
+    });
+
+    ctx.reply(format!(
+        "Survivor-Tipp für {matchday}: {} {} ({})",
+        team.name, team.flag, game.name
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn survivor_tabelle(ctx: PoiseContext<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let d = ctx.data().read().await;
+
+    let mut table = AsciiTable::default();
+    table.column(0).set_header("Spieler");
+    table.column(1).set_header("Status");
+    table.column(2).set_header("Tipps");
+
+    let mut rows = Vec::new();
+    for user in participants(&d) {
+        let status = status(&d, user);
+        let picks = d
+            .survivor
+            .iter()
+            .filter(|p| p.user == user)
+            .map(|p| p.team_iso.clone())
+            .collect::<Vec<_>>();
+        rows.push((status, user, picks));
+    }
+    rows.sort_by_key(|(s, _, p)| (*s != Status::Alive, std::cmp::Reverse(p.len())));
+
+    let mut data: Vec<Vec<String>> = Vec::new();
+    for (status, user, picks) in rows {
+        let name = user
+            .to_user(ctx.http())
+            .await
+            .map(|u| u.name)
+            .unwrap_or("UNKNOWN".to_string());
+        let status = match status {
+            Status::Alive => "Dabei".to_string(),
+            Status::Out { matchday, reason } => format!("Raus ({matchday}: {reason})"),
+        };
+        data.push(vec![name, status, picks.join(" ")]);
+    }
+
+    let table_string = table.format(data);
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content(format!("# Survivor\n```\n{table_string}\n```")),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Fixtures shared by the unit tests.
+
+use axum::Router;
+use chrono::NaiveDate;
+
+use crate::data::Game;
+
+/// A game without result between Germany and Scotland.
+pub fn game(short: &str) -> Game {
+    Game {
+        name: format!("Spiel {short}"),
+        short: short.to_string(),
+        team1_iso: "DEU".to_string(),
+        team2_iso: "GB-SCT".to_string(),
+        start_time: NaiveDate::from_ymd_opt(2024, 6, 14)
+            .unwrap()
+            .and_hms_opt(21, 0, 0)
+            .unwrap(),
+        result: None,
+        modifier: 1,
+        matchday: None,
+        external_id: None,
+    }
+}
+
+/// Serves `app` on a free local port and returns its base URL.
+pub async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    url
+}
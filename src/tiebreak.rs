@@ -0,0 +1,99 @@
+use ascii_table::AsciiTable;
+use itertools::Itertools;
+use poise::{Command, CreateReply};
+
+use crate::cmds::get_now;
+use crate::data::{Data, DataInter};
+use crate::scoring::{all_games_finished, total_goals};
+use crate::{Error, PoiseContext};
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![torfrage()]
+}
+
+/// Guesses can be changed until the opener kicks off.
+fn locked(d: &DataInter) -> bool {
+    d.games
+        .iter()
+        .map(|g| g.start_time)
+        .min()
+        .is_some_and(|t| t <= get_now())
+}
+
+#[poise::command(slash_command, required_permissions = "SEND_MESSAGES")]
+async fn torfrage(
+    ctx: PoiseContext<'_>,
+    #[description = "Wie viele Tore fallen im ganzen Turnier? (leer lassen zum Anzeigen)"]
+    tore: Option<u32>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user = ctx.author().id;
+    let mut d = ctx.data().lock().await;
+
+    if let Some(tore) = tore {
+        if locked(&d) {
+            ctx.reply("Das Turnier hat bereits begonnen!").await?;
+            return Ok(());
+        }
+        d.goal_guesses.insert(user, tore);
+        ctx.reply(format!(
+            "Antwort gespeichert: {tore} Tore. Bei Punktgleichheit gewinnt, wer näher dran ist."
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let goals = total_goals(&d);
+    let mut lines = vec![
+        "# Torfrage".to_string(),
+        if all_games_finished(&d) {
+            format!("Gefallene Tore: {goals}")
+        } else {
+            format!("Bisher gefallene Tore: {goals} (entscheidet erst nach dem letzten Spiel)")
+        },
+        format!(
+            "Deine Antwort: {}",
+            d.goal_guesses
+                .get(&user)
+                .map(|g| g.to_string())
+                .unwrap_or("-".to_string())
+        ),
+    ];
+
+    // Other answers stay hidden until they can no longer be changed.
+    if locked(&d) && !d.goal_guesses.is_empty() {
+        let mut table = AsciiTable::default();
+        table.column(0).set_header("Spieler");
+        table.column(1).set_header("Tore");
+        table.column(2).set_header("Abstand");
+
+        let mut data: Vec<Vec<String>> = Vec::new();
+        for (u, g) in d
+            .goal_guesses
+            .iter()
+            .sorted_by_key(|(u, g)| ((**g as i64 - goals as i64).abs(), **u))
+        {
+            let name = u
+                .to_user(ctx.http())
+                .await
+                .map(|u| u.name)
+                .unwrap_or("UNKNOWN".to_string());
+            data.push(vec![
+                name,
+                g.to_string(),
+                (*g as i64 - goals as i64).abs().to_string(),
+            ]);
+        }
+        lines.push(format!("```\n{}\n```", table.format(data)));
+    }
+
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content(lines.join("\n")),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use itertools::Itertools;
+use poise::Command;
+use serenity::all::UserId;
+
+use crate::data::{Bet, Data, DataInter, Game};
+use crate::scoring::Tally;
+use crate::{Error, PoiseContext};
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![set_virtual_participants()]
+}
+
+/// The most common tip on a game, ties go to the lower score.
+fn crowd_tip(d: &DataInter, game: &Game) -> Option<(u16, u16)> {
+    d.bets
+        .get(&game.short)?
+        .iter()
+        .map(|b| (b.team1, b.team2))
+        .counts()
+        .into_iter()
+        .sorted_by_key(|(tip, count)| (std::cmp::Reverse(*count), *tip))
+        .map(|(tip, _)| tip)
+        .next()
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Virtual {
+    Crowd,
+    Draw,
+    HomeWin,
+}
+
+impl Virtual {
+    fn name(&self) -> &'static str {
+        match self {
+            Virtual::Crowd => "🤖 Schwarm",
+            Virtual::Draw => "🤖 Immer 1:1",
+            Virtual::HomeWin => "🤖 Heimsieg 2:1",
+        }
+    }
+
+    fn tip(&self, d: &DataInter, game: &Game) -> Option<(u16, u16)> {
+        match self {
+            Virtual::Crowd => crowd_tip(d, game),
+            Virtual::Draw => Some((1, 1)),
+            Virtual::HomeWin => Some((2, 1)),
+        }
+    }
+}
+
+/// Tallies of the computed comparison participants. They only exist here
+/// and never show up in `DataInter::bets`.
+pub fn virtual_tallies(d: &DataInter) -> Vec<(&'static str, Tally)> {
+    [Virtual::Crowd, Virtual::Draw, Virtual::HomeWin]
+        .iter()
+        .map(|v| {
+            let mut tally = Tally::default();
+            for game in d.games.iter().filter(|g| g.result.is_some()) {
+                if let Some((team1, team2)) = v.tip(d, game) {
+                    let bet = Bet {
+                        // Never stored, scoring does not look at the user.
+                        user: UserId::new(1),
+                        team1,
+                        team2,
+                        joker: false,
+                        weight: None,
+                    };
+                    tally.add_bet(d, game, &bet);
+                }
+            }
+            (v.name(), tally)
+        })
+        .collect()
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn set_virtual_participants(
+    ctx: PoiseContext<'_>,
+    #[description = "Schwarm und Vergleichstipper in der Rangliste anzeigen"] enabled: bool,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let mut d = ctx.data().lock().await;
+    d.settings.virtual_participants = enabled;
+
+    ctx.reply(if enabled {
+        "Virtuelle Teilnehmer werden in der Rangliste angezeigt."
+    } else {
+        "Virtuelle Teilnehmer ausgeblendet."
+    })
+    .await?;
+
+    Ok(())
+}
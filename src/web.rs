@@ -0,0 +1,159 @@
+use axum::extract::State;
+use axum::response::Html;
+use itertools::Itertools;
+use serenity::all::UserId;
+
+use crate::api::leaderboard_entries;
+use crate::cmds::{format_tip, get_now};
+use crate::data::{Data, DataInter, Game};
+
+const REFRESH_SECONDS: u32 = 60;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 0 auto; padding: 1em; max-width: 60em; }
+.scroll { overflow-x: auto; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+th, td { padding: 0.3em 0.6em; border-bottom: 1px solid #ddd; text-align: left; white-space: nowrap; }
+th { background: #f4f4f4; }
+";
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn name(d: &DataInter, user: UserId) -> String {
+    escape(d.names.get(&user).map(|n| n.as_str()).unwrap_or("UNKNOWN"))
+}
+
+fn team(d: &DataInter, iso: &str) -> String {
+    escape(
+        &d.teams
+            .iter()
+            .find(|t| t.iso == iso)
+            .map(|t| format!("{} {}", t.flag, t.name))
+            .unwrap_or(iso.to_string()),
+    )
+}
+
+fn table(headers: &[String], rows: &[Vec<String>]) -> String {
+    format!(
+        "<div class=\"scroll\"><table><tr>{}</tr>{}</table></div>",
+        headers.iter().map(|h| format!("<th>{h}</th>")).join(""),
+        rows.iter()
+            .map(|r| format!(
+                "<tr>{}</tr>",
+                r.iter().map(|c| format!("<td>{c}</td>")).join("")
+            ))
+            .join("")
+    )
+}
+
+fn game_row(d: &DataInter, game: &Game) -> Vec<String> {
+    vec![
+        escape(&game.name),
+        team(d, &game.team1_iso),
+        game.result
+            .as_ref()
+            .map(|r| format!("{}:{}", r.0, r.1))
+            .unwrap_or("-:-".to_string()),
+        team(d, &game.team2_iso),
+        game.start_time.format("%d.%m. %H:%M").to_string(),
+    ]
+}
+
+fn render(d: &DataInter) -> String {
+    let leaderboard = leaderboard_entries(d)
+        .iter()
+        .map(|e| {
+            vec![
+                format!("{}.", e.rank),
+                name(d, e.user),
+                e.points.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let games = d
+        .games
+        .iter()
+        .sorted_by_key(|g| g.start_time)
+        .collect::<Vec<_>>();
+    let game_headers = ["Spiel", "Heim", "", "Gast", "Anpfiff"].map(String::from);
+    let upcoming = games
+        .iter()
+        .filter(|g| g.start_time > get_now())
+        .map(|g| game_row(d, g))
+        .collect::<Vec<_>>();
+    let finished = games
+        .iter()
+        .filter(|g| g.result.is_some())
+        .rev()
+        .map(|g| game_row(d, g))
+        .collect::<Vec<_>>();
+
+    // Only started games, tips stay hidden until kickoff.
+    let started = games
+        .iter()
+        .filter(|g| g.start_time <= get_now())
+        .collect::<Vec<_>>();
+    let users = started
+        .iter()
+        .flat_map(|g| d.bets.get(&g.short).into_iter().flatten().map(|b| b.user))
+        .unique()
+        .sorted_by_key(|u| name(d, *u))
+        .collect::<Vec<_>>();
+    let tips = users
+        .iter()
+        .map(|u| {
+            let mut row = vec![name(d, *u)];
+            row.extend(started.iter().map(|g| {
+                d.bets
+                    .get(&g.short)
+                    .and_then(|bets| bets.iter().find(|b| b.user == *u))
+                    .map(format_tip)
+                    .unwrap_or("-:-".to_string())
+            }));
+            row
+        })
+        .collect::<Vec<_>>();
+    let mut tip_headers = vec!["Spieler".to_string()];
+    tip_headers.extend(started.iter().map(|g| escape(&g.short)));
+
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"de\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<meta http-equiv=\"refresh\" content=\"{REFRESH_SECONDS}\">
+<title>Tippspiel</title>
+<style>{STYLE}</style>
+</head>
+<body>
+<h1>Rangliste</h1>
+{}
+<h2>Nächste Spiele</h2>
+{}
+<h2>Ergebnisse</h2>
+{}
+<h2>Tipps</h2>
+<p>* = Joker (doppelte Punkte), xN = Vertrauensgewicht</p>
+{}
+<p><small>Stand: {}</small></p>
+</body>
+</html>",
+        table(&["#", "Spieler", "Punkte"].map(String::from), &leaderboard),
+        table(&game_headers, &upcoming),
+        table(&game_headers, &finished),
+        table(&tip_headers, &tips),
+        get_now().format("%d.%m.%Y %H:%M"),
+    )
+}
+
+pub async fn leaderboard_page(State(data): State<Data>) -> Html<String> {
+    let d = data.read().await;
+    Html(render(&d))
+}
@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use poise::Command;
+use serde::Serialize;
+
+use crate::api::{leaderboard_entries, LeaderboardEntry};
+use crate::cmds::get_now;
+use crate::data::{Data, DataInter, Game};
+use crate::{Error, PoiseContext};
+
+const RETRIES: u32 = 4;
+const FIRST_BACKOFF: Duration = Duration::from_secs(1);
+const CLOSING_MINUTES: i64 = 30;
+
+pub fn get_cmds() -> Vec<Command<Data, Error>> {
+    vec![add_webhook(), remove_webhook(), test_webhook()]
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    GameCreated { game: Game },
+    BetWindowClosing { game: Game, minutes: i64 },
+    ResultEntered { game: Game },
+    LeaderboardChanged { leaderboard: Vec<LeaderboardEntry> },
+    GlobalBetResolved { name: String, result: Vec<String> },
+    Test,
+}
+
+async fn send(client: &reqwest::Client, url: &str, event: &Event) -> Result<(), reqwest::Error> {
+    client
+        .post(url)
+        .json(event)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Retries failed deliveries, waiting `backoff` before the first retry and doubling it after.
+async fn deliver(url: String, event: Event, mut backoff: Duration) {
+    let client = reqwest::Client::new();
+    for attempt in 0..=RETRIES {
+        match send(&client, &url, &event).await {
+            Ok(()) => return,
+            Err(why) if attempt < RETRIES => {
+                eprintln!("Webhook {url} failed, retrying in {backoff:?}: {why}");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            Err(why) => eprintln!("Webhook {url} failed, giving up: {why}"),
+        }
+    }
+}
+
+/// Sends the event to all configured webhooks in the background.
+pub fn emit(d: &DataInter, event: Event) {
+    for url in &d.settings.webhooks {
+        tokio::spawn(deliver(url.clone(), event.clone(), FIRST_BACKOFF));
+    }
+}
+
+/// Emits the new leaderboard if it differs from `before`.
+pub fn emit_leaderboard(d: &DataInter, before: Vec<LeaderboardEntry>) {
+    let leaderboard = leaderboard_entries(d);
+    if leaderboard != before {
+        emit(d, Event::LeaderboardChanged { leaderboard });
+    }
+}
+
+/// Announces games whose betting window closes soon, once per game and run.
+pub async fn watch_bet_windows(data: Data) {
+    let mut announced: HashSet<String> = HashSet::new();
+    let mut interval = tokio::time::interval(Duration::from_secs(60));
+    loop {
+        interval.tick().await;
+
+        let d = data.read().await;
+        if d.settings.webhooks.is_empty() {
+            continue;
+        }
+        let deadline = get_now() + chrono::Duration::minutes(CLOSING_MINUTES);
+        for game in d
+            .games
+            .iter()
+            .filter(|g| g.start_time > get_now() && g.start_time <= deadline)
+        {
+            if announced.insert(game.short.clone()) {
+                emit(
+                    &d,
+                    Event::BetWindowClosing {
+                        game: game.clone(),
+                        minutes: (game.start_time - get_now()).num_minutes(),
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn add_webhook(
+    ctx: PoiseContext<'_>,
+    #[description = "URL, an die Ereignisse als JSON gesendet werden"] url: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    reqwest::Url::parse(&url).map_err(|_| "Ungültige URL!")?;
+
+    let mut d = ctx.data().lock().await;
+    if !d.settings.webhooks.contains(&url) {
+        d.settings.webhooks.push(url);
+    }
+
+    ctx.reply(format!("Webhooks:\n{}", d.settings.webhooks.join("\n")))
+        .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn remove_webhook(
+    ctx: PoiseContext<'_>,
+    #[description = "Die URL, die entfernt werden soll"] url: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let mut d = ctx.data().lock().await;
+    d.settings.webhooks.retain(|w| *w != url);
+
+    ctx.reply(format!("Webhook {url} entfernt.")).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn test_webhook(ctx: PoiseContext<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let d = ctx.data().read().await;
+    emit(&d, Event::Test);
+
+    ctx.reply(format!(
+        "Test-Ereignis an {} Webhook(s) gesendet.",
+        d.settings.webhooks.len()
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::testing::serve;
+
+    type Received = Arc<Mutex<Vec<Value>>>;
+
+    fn game() -> Game {
+        Game {
+            result: Some((5, 1, String::new())),
+            ..crate::testing::game("A1")
+        }
+    }
+
+    /// Fails the first two deliveries, accepts from the third on.
+    async fn flaky_hook(State(received): State<Received>, Json(body): Json<Value>) -> StatusCode {
+        let mut received = received.lock().unwrap();
+        received.push(body);
+        if received.len() < 3 {
+            StatusCode::INTERNAL_SERVER_ERROR
+        } else {
+            StatusCode::OK
+        }
+    }
+
+    #[tokio::test]
+    async fn deliver_retries_after_errors() {
+        let received = Received::default();
+        let app = Router::new()
+            .route("/hook", post(flaky_hook))
+            .with_state(received.clone());
+        let url = format!("{}/hook", serve(app).await);
+
+        deliver(
+            url,
+            Event::ResultEntered { game: game() },
+            Duration::from_millis(10),
+        )
+        .await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        assert!(received.iter().all(|body| body == &received[0]));
+        assert_eq!(received[0]["event"], "result_entered");
+        assert_eq!(received[0]["game"]["short"], "A1");
+        assert_eq!(received[0]["game"]["result"], json!([5, 1, ""]));
+    }
+
+    #[test]
+    fn events_are_tagged() {
+        let closing = serde_json::to_value(Event::BetWindowClosing {
+            game: game(),
+            minutes: 30,
+        })
+        .unwrap();
+        assert_eq!(closing["event"], "bet_window_closing");
+        assert_eq!(closing["minutes"], 30);
+        assert_eq!(closing["game"]["team1_iso"], "DEU");
+
+        let resolved = serde_json::to_value(Event::GlobalBetResolved {
+            name: "Weltmeister".to_string(),
+            result: vec!["ESP".to_string()],
+        })
+        .unwrap();
+        assert_eq!(
+            resolved,
+            json!({"event": "global_bet_resolved", "name": "Weltmeister", "result": ["ESP"]})
+        );
+
+        assert_eq!(
+            serde_json::to_value(Event::Test).unwrap(),
+            json!({"event": "test"})
+        );
+    }
+}